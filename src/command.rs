@@ -1,217 +1,549 @@
-use colored::Colorize;
+use crate::completions::{self, CompletionShell};
+use crate::config::types::{GemonBodyType, GemonMethodType, GemonStatusRanges, GemonType};
+use crate::printer::filter::Filter;
+use clap::{Args, CommandFactory, Parser, Subcommand};
+use clap_complete::ArgValueCandidates;
+use std::{collections::HashSet, path::PathBuf};
 
-use crate::EmptyResult;
+const NOTES: &str = "\
+Notes:
+  * Values with spaces need to be enclosed in quotation marks \" or '
+  * Saving a REST request which accepts a body creates an empty body.json next to it where the body can be put
+  * WEBSOCKET requests send the body as the initial frame, a JSON array body is sent as one frame per element
+  * PROTO requests encode the JSON body with the request message of the gRPC method and decode the reply back to JSON
+  * Environment values can be used in the uri, headers, form data and body, e.g. with a saved 'base_uri': -u {base_uri}/path
+  * The older forms such as -u=(uri), -rf=(file), -s=(name), -e=(env::key::value) or print-env still work";
 
-#[derive(Debug)]
-pub enum Form {
-    Short,
-    Long,
+/// Gemon, a command line API client for REST, WebSocket and gRPC requests.
+///
+/// Without a subcommand the request described by the options is sent, e.g.
+/// `gemon -t REST -m GET -u https://api.com/users`.
+#[derive(Debug, Parser)]
+#[command(
+    name = "gemon",
+    disable_version_flag = true,
+    after_long_help = NOTES,
+    args_override_self = true
+)]
+pub struct GemonCli {
+    #[command(subcommand)]
+    pub command: Option<GemonSubcommand>,
+
+    /// Print gemon version info
+    #[arg(short = 'v', long)]
+    pub version: bool,
+
+    /// Project folder, or its gemon.json, to use instead of the closest one from the current
+    /// folder up. Also read from GEMON_PROJECT
+    #[arg(long, global = true, value_name = "PATH")]
+    pub project: Option<PathBuf>,
+
+    #[command(flatten, next_help_heading = "Request Options")]
+    pub request: RequestOptions,
 }
 
-#[derive(Debug)]
-pub enum GemonCommand {
-    Invalid,
-    Help,
-    Version,
+#[derive(Debug, Subcommand)]
+pub enum GemonSubcommand {
+    /// Initialize the current folder into a gemon project
     Init,
-    PrintEnvAll,
-    PrintEnv,
-    PrintLastCall,
-    TypeRest,
-    TypeWebsocket,
-    TypeProto,
-    MethodGet,
-    MethodPost,
-    MethodDelete,
-    MethodPut,
-    MethodPatch,
-    File,
-    LogResponse,
-    AlsoPrintToTerminal,
-    Uri(String, Form),
-    Header(String, Form),
-    Body(String, Form),
-    FormData(String, Form),
-    ResponseFile(String, Form),
-    Save(String, Form),
-    Call(String, Form),
-    SaveAndCall(String, Form),
-    Delete(String, Form),
-    RemoveEnv(String, Form),
-    AddEnv(String, Form),
-    RemoveEnvValue(String, Form),
-    SelectEnv(String, Form),
-    AddAuthorization(String, Form),
-    RemoveAuthorization,
-    Secure,
+    /// Open the interactive terminal user interface, also `--tui`, `--interactive` or `-i`
+    Tui,
+    /// Call a saved request, the options given override the saved ones
+    Call {
+        /// Name the request was saved with
+        #[arg(add = ArgValueCandidates::new(completions::saved_requests))]
+        name: String,
+    },
+    /// Print the last call response that was stored in the file
+    Print,
+    /// Call every saved request with an assertions.json (or the listed ones), check the assertions
+    /// and exit with an error if any fail
+    Test {
+        /// Requests to test, e.g. `login,users`
+        #[arg(value_delimiter = ',', add = ArgValueCandidates::new(completions::saved_requests))]
+        names: Vec<String>,
+    },
+    /// Call the steps of runs/(name).json in order, capturing response values into the selected
+    /// environment
+    Run {
+        /// Name of the run file without .json
+        name: String,
+    },
+    /// Save, save and call or delete requests of the project
+    #[command(subcommand)]
+    Request(RequestCommand),
+    /// Manage the environments and their values
+    #[command(subcommand)]
+    Env(EnvCommand),
+    /// Manage the authorization of the selected environment, or the default one without an
+    /// environment
+    #[command(subcommand)]
+    Auth(AuthCommand),
+    /// Manage the TLS settings of the selected environment, or the default ones
+    #[command(subcommand)]
+    Tls(SettingCommand),
+    /// Manage the proxy of the selected environment, or the default one
+    #[command(subcommand)]
+    Proxy(SettingCommand),
+    /// Print or clear the cookies stored for the selected environment
+    #[command(subcommand)]
+    Cookies(CookiesCommand),
+    /// Import a Postman collection or environment, or a curl command
+    #[command(subcommand)]
+    Import(ImportCommand),
+    /// Export a saved request
+    #[command(subcommand)]
+    Export(ExportCommand),
+    /// Print the completion script of a shell, e.g. `source <(gemon completions bash)` in
+    /// ~/.bashrc, it completes commands, flags and the saved request and environment names
+    Completions {
+        #[arg(value_enum)]
+        shell: CompletionShell,
+    },
 }
 
-impl From<String> for GemonCommand {
-    fn from(cmd: String) -> Self {
-        match cmd.as_str() {
-            "-h" | "--help" => GemonCommand::Help,
-            "-v" | "--version" => GemonCommand::Version,
-            "init" => GemonCommand::Init,
-            "print-env-all" => GemonCommand::PrintEnvAll,
-            "print-env" => GemonCommand::PrintEnv,
-            "print" => GemonCommand::PrintLastCall,
-            "-t=REST" | "--type=REST" => GemonCommand::TypeRest,
-            "-t=WEBSOCKET" | "--type=WEBSOCKET" => GemonCommand::TypeWebsocket,
-            "-t=PROTO" | "--type=PROTO" => GemonCommand::TypeProto,
-            "-m=GET" | "--method=GET" => GemonCommand::MethodGet,
-            "-m=POST" | "--method=POST" => GemonCommand::MethodPost,
-            "-m=DELETE" | "--method=DELETE" => GemonCommand::MethodDelete,
-            "-m=PUT" | "--method=PUT" => GemonCommand::MethodPut,
-            "-m=PATCH" | "--method=PATCH" => GemonCommand::MethodPatch,
-            "-f" | "--file" => GemonCommand::File,
-            "-l" | "--log" => GemonCommand::LogResponse,
-            "-p" | "--print" => GemonCommand::AlsoPrintToTerminal,
-            "-sec" | "--secure" => GemonCommand::Secure,
-            "-r-auth" | "--remove-authorization" => GemonCommand::RemoveAuthorization,
-            s if s.starts_with("-auth=") => GemonCommand::AddAuthorization(cmd, Form::Short),
-            s if s.starts_with("--authorization=") => {
-                GemonCommand::AddAuthorization(cmd, Form::Long)
-            }
-            s if s.starts_with("-u=") => GemonCommand::Uri(cmd, Form::Short),
-            s if s.starts_with("--uri=") => GemonCommand::Uri(cmd, Form::Long),
-            s if s.starts_with("-h=") => GemonCommand::Header(cmd, Form::Short),
-            s if s.starts_with("--header=") => GemonCommand::Header(cmd, Form::Long),
-            s if s.starts_with("-b=") => GemonCommand::Body(cmd, Form::Short),
-            s if s.starts_with("--body=") => GemonCommand::Body(cmd, Form::Long),
-            s if s.starts_with("-fd=") => GemonCommand::FormData(cmd, Form::Short),
-            s if s.starts_with("--form-data=") => GemonCommand::FormData(cmd, Form::Long),
-            s if s.starts_with("-rf=") => GemonCommand::ResponseFile(cmd, Form::Short),
-            s if s.starts_with("--response-file=") => GemonCommand::ResponseFile(cmd, Form::Long),
-            s if s.starts_with("-s=") => GemonCommand::Save(cmd, Form::Short),
-            s if s.starts_with("--save=") => GemonCommand::Save(cmd, Form::Long),
-            s if s.starts_with("-c=") => GemonCommand::Call(cmd, Form::Short),
-            s if s.starts_with("--call=") => GemonCommand::Call(cmd, Form::Short),
-            s if s.starts_with("-sc=") => GemonCommand::SaveAndCall(cmd, Form::Long),
-            s if s.starts_with("--save-and-call=") => GemonCommand::SaveAndCall(cmd, Form::Long),
-            s if s.starts_with("-d=") => GemonCommand::Delete(cmd, Form::Short),
-            s if s.starts_with("--delete=") => GemonCommand::Delete(cmd, Form::Long),
-            s if s.starts_with("-ed=") => GemonCommand::RemoveEnv(cmd, Form::Short),
-            s if s.starts_with("--env-delete=") => GemonCommand::RemoveEnv(cmd, Form::Long),
-            s if s.starts_with("-e=") => GemonCommand::AddEnv(cmd, Form::Short),
-            s if s.starts_with("--env=") => GemonCommand::AddEnv(cmd, Form::Long),
-            s if s.starts_with("-edv=") => GemonCommand::RemoveEnvValue(cmd, Form::Short),
-            s if s.starts_with("-env-delete-value=") => {
-                GemonCommand::RemoveEnvValue(cmd, Form::Long)
-            }
-            s if s.starts_with("-se=") => GemonCommand::SelectEnv(cmd, Form::Short),
-            s if s.starts_with("--select-env=") => GemonCommand::SelectEnv(cmd, Form::Long),
-            _ => GemonCommand::Invalid,
-        }
-    }
+#[derive(Debug, Subcommand)]
+pub enum RequestCommand {
+    /// Save the request described by the options so it can be called later
+    Save {
+        #[arg(add = ArgValueCandidates::new(completions::saved_requests))]
+        name: String,
+    },
+    /// Save the request described by the options and call it
+    SaveAndCall {
+        #[arg(add = ArgValueCandidates::new(completions::saved_requests))]
+        name: String,
+    },
+    /// Remove a saved request
+    Delete {
+        #[arg(add = ArgValueCandidates::new(completions::saved_requests))]
+        name: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum EnvCommand {
+    /// Print all environments with their values
+    List,
+    /// Print the values of the selected environment
+    Show,
+    /// Select the environment the values are taken from
+    Select {
+        #[arg(add = ArgValueCandidates::new(completions::environments))]
+        env: String,
+    },
+    /// Save a value into an environment, the environment is created when it does not exist
+    Set {
+        #[arg(add = ArgValueCandidates::new(completions::environments))]
+        env: String,
+        key: String,
+        #[arg(allow_hyphen_values = true)]
+        value: String,
+    },
+    /// Remove a value from an environment
+    Unset {
+        #[arg(add = ArgValueCandidates::new(completions::environments))]
+        env: String,
+        key: String,
+    },
+    /// Remove an environment
+    Delete {
+        #[arg(add = ArgValueCandidates::new(completions::environments))]
+        env: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AuthCommand {
+    /// Set the authorization, e.g. 'Bearer token...'
+    Set {
+        #[arg(allow_hyphen_values = true)]
+        value: String,
+    },
+    /// Remove the authorization
+    Remove,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SettingCommand {
+    /// Set one setting: cert, key, cacert, insecure or min-version for TLS, url, username,
+    /// password or no-proxy for a proxy
+    Set {
+        setting: String,
+        #[arg(allow_hyphen_values = true)]
+        value: String,
+    },
+    /// Remove all settings
+    Remove,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum CookiesCommand {
+    /// Print the stored cookies
+    Show,
+    /// Remove the stored cookies
+    Clear,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ImportCommand {
+    /// Import a Postman v2.1 collection as saved requests, or an environment export as an
+    /// environment
+    Postman {
+        #[arg(value_name = "FILE")]
+        path: String,
+    },
+    /// Read a curl command from the standard input and save it as a REST request
+    Curl { name: String },
 }
 
-impl GemonCommand {
-    pub fn print_all() -> EmptyResult {
-        let info_separator = "------------------Gemon Info--------------------";
-        println!("{}", info_separator.red().bold());
-        GemonCommand::print_info("* In the commands displayed below the parenthesis () are to show that instead of the values show here you would provide your own values. The parenthesis themselves are also not needed");
-        GemonCommand::print_info("* If you need to pass values that have spaces you need to enclose the whole value in quotation marks \" or '");
-        GemonCommand::print_info("* When saving a rest response which accepts a request body, inside the folder with the name of the request an empty body.json will be created where you can put the request body if needed");
-        GemonCommand::print_info("* Gemon allows env variables to be saved and then used, if an variabled called 'base_uri' is saved it can be used: -u={base_uri}/path .Env variables can be used in uri, headers, form data and request body");
-        let commands_separator = "------------------Gemon Commands--------------------";
-        println!("{}", commands_separator.red().bold());
-        GemonCommand::print_command(
-            "-h | --help",
-            "Print the list of command options in terminal",
-        );
-        GemonCommand::print_command("-v | --version", "Pring gemon version info");
-        GemonCommand::print_command(
-            "tui | --tui | -i | --interactive",
-            "Open the interactive terminal user interface",
-        );
-        GemonCommand::print_command("init", "Initialize current folder into a gemon project");
-        GemonCommand::print_command(
-            "print-env-all",
-            "Print all environments with their assosiated variables",
-        );
-        GemonCommand::print_command("print-env", "Print values of the current environment");
-        GemonCommand::print_command(
-            "print",
-            "Print the last call response that was stored in the file",
-        );
-        GemonCommand::print_command("-t=(REST | WEBSOCKET | PROTO)", "Set the type of request");
-        GemonCommand::print_command(
-            "-m=(GET | POST | DELETE | PUT | PATCH)",
-            "Set the type of REST method | Required: -t=REST",
-        );
-        GemonCommand::print_command(
-            "-f | --file",
-            "Save the response of the call to the default response.json file",
-        );
-        GemonCommand::print_command(
-            "-l | --log",
-            "If set the response file name is tagged with the timestamp",
-        );
-        GemonCommand::print_command(
-            "-p | --print",
-            "Save the response to the response file but also print it to terminal",
-        );
-        GemonCommand::print_command(
-            "-u=(https://api.com:8080) | --uri=(https://api.com:8080)",
-            "Set the URI of the request",
-        );
-        GemonCommand::print_command(
-            "-h=(key::value) | --header=(key::value)",
-            "Set a header to the request",
-        );
-        GemonCommand::print_command(
-            "-b=('{\"name\": \"some name\"}') | --body=('{\"name\": \"some name\"}')",
-            "Set the body of the request",
-        );
-        GemonCommand::print_command(
-            "-fd=(key:value) | --form-data=(key:value)",
-            "Set a form data parameter to the request",
-        );
-        GemonCommand::print_command(
-            "-rf=(file_name.json) | --response-file=(file_name.json)",
-            "Print the response to the provided file name",
-        );
-        GemonCommand::print_command("-sec | --secure", "Mark request that it needs to be authorized, authorization is either taken from project or from specific header");
-        GemonCommand::print_command(
-            "-s | --save",
-            "Save request into the project so it can be called later",
-        );
-        GemonCommand::print_command(
-            "-c=(login) | --call=(login)",
-            "Calls a previously saved request by providing the name with which it was saved",
-        );
-        GemonCommand::print_command(
-            "-sc=(login) | --save-and-call=(login)",
-            "Simultaneusly it saves a new request and calles it",
-        );
-        GemonCommand::print_command(
-            "-d=(login) | --delete=(login)",
-            "Removes a previously saved request",
-        );
-        GemonCommand::print_command("-e=(int::base_uri::https://api.com) | --env=(int::base_uri::https://api.com)", "Saves a new env value into the project. If the env on which the new value it is being saved does not exist the environment is also created");
-        GemonCommand::print_command(
-            "-ed=(int) | --env-delete=(int)",
-            "Removes a previously saved environment",
-        );
-        GemonCommand::print_command(
-            "-edv=(int::key) | --env-delete-value=(int::key)",
-            "Removes an env variable from one of the environments",
-        );
-        GemonCommand::print_command(
-            "-se=(int) | --select-env=(int)",
-            "Set an previously created environment as the current environment",
-        );
-        GemonCommand::print_command("-r-auth | --remove-authorization", "Removes authorziation set for current environment, if no environemnt is set removes the default authoriziation used without environment");
-        GemonCommand::print_command("-auth='Bearer token...' | --authorization='Bearer token...'", "Set authorization for current environment, if no environment is selected it set the default authorization without environment");
-
-        Ok(())
+#[derive(Debug, Subcommand)]
+pub enum ExportCommand {
+    /// Print a saved REST request as a curl command with the selected environment values filled
+    /// in, add --secure to include the authorization
+    Curl {
+        #[arg(add = ArgValueCandidates::new(completions::saved_requests))]
+        name: String,
+    },
+}
+
+/// The options describing a request and its output, given before or after a subcommand.
+#[derive(Debug, Args)]
+pub struct RequestOptions {
+    /// Type of the request
+    #[arg(
+        short = 't',
+        long = "type",
+        global = true,
+        value_name = "TYPE",
+        ignore_case = true
+    )]
+    pub gemon_type: Option<GemonType>,
+
+    /// REST method, any other HTTP verb is sent as a custom method
+    #[arg(short, long, global = true)]
+    pub method: Option<GemonMethodType>,
+
+    /// URI of the request
+    #[arg(short, long, global = true)]
+    pub uri: Option<String>,
+
+    /// Header of the request, can be repeated
+    #[arg(
+        short = 'H',
+        long = "header",
+        global = true,
+        value_name = "KEY::VALUE",
+        value_parser = key_value_pair
+    )]
+    pub headers: Vec<(String, String)>,
+
+    /// Body of the request
+    #[arg(short, long, global = true, allow_hyphen_values = true)]
+    pub body: Option<String>,
+
+    /// Read the body from a file, files that are not UTF-8 text are sent as a binary body
+    #[arg(long, global = true, value_name = "FILE", conflicts_with = "body")]
+    pub body_file: Option<String>,
+
+    /// Read the body from the standard input
+    #[arg(long, global = true, conflicts_with_all = ["body", "body_file"])]
+    pub body_stdin: bool,
+
+    /// How the body is encoded and the Content-Type sent, a Content-Type header overrides it
+    #[arg(long, global = true)]
+    pub body_type: Option<GemonBodyType>,
+
+    /// Form data parameter, can be repeated. A value like @./file.png;type=image/png uploads a
+    /// file and makes the body multipart when no --body-type is given, \@ starts a text value
+    /// with @
+    #[arg(
+        short = 'F',
+        long = "form-data",
+        global = true,
+        value_name = "KEY::VALUE",
+        value_parser = key_value_pair
+    )]
+    pub form_data: Vec<(String, String)>,
+
+    /// Save the response to the response.json file of the called request
+    #[arg(short, long, global = true)]
+    pub file: bool,
+
+    /// Tag the response file name with the timestamp
+    #[arg(short, long, global = true)]
+    pub log: bool,
+
+    /// Save the response to the response file but also print it to the terminal
+    #[arg(short, long, global = true)]
+    pub print: bool,
+
+    /// Write the response to this file
+    #[arg(short = 'o', long, global = true, value_name = "FILE")]
+    pub response_file: Option<String>,
+
+    /// Print the status line, elapsed time and response headers before the body
+    #[arg(short = 'S', long, global = true)]
+    pub include: bool,
+
+    /// Like --include but also print the request line and request headers
+    #[arg(long, global = true)]
+    pub verbose: bool,
+
+    /// Print only the values a jq-like filter picks out of a JSON response, e.g. `.items[].id`
+    #[arg(long, global = true)]
+    pub filter: Option<Filter>,
+
+    /// Exit with code 3 when the response status is 4xx or 5xx, or in the given ranges
+    #[arg(
+        long,
+        global = true,
+        value_name = "RANGES",
+        num_args = 0..=1,
+        require_equals = true
+    )]
+    pub fail: Option<Option<GemonStatusRanges>>,
+
+    /// Mark the request as one that needs to be authorized with the project authorization
+    #[arg(long, global = true)]
+    pub secure: bool,
+
+    /// Milliseconds to wait for the connection to be established
+    #[arg(long, global = true, value_name = "MS")]
+    pub connect_timeout: Option<u64>,
+
+    /// Milliseconds the whole request may take before it fails
+    #[arg(long, global = true, value_name = "MS")]
+    pub timeout: Option<u64>,
+
+    /// Follow at most this many redirects, defaults to 10
+    #[arg(long, global = true, value_name = "COUNT")]
+    pub max_redirects: Option<usize>,
+
+    /// Do not follow redirects, same as --max-redirects 0
+    #[arg(long, global = true, conflicts_with = "max_redirects")]
+    pub no_follow: bool,
+
+    /// Retry connection errors, timeouts and the --retry-status statuses this many times
+    #[arg(long, global = true, value_name = "COUNT")]
+    pub retries: Option<u32>,
+
+    /// Milliseconds before the first retry, doubled for every next one, defaults to 500
+    #[arg(long, global = true, value_name = "MS")]
+    pub retry_backoff: Option<u64>,
+
+    /// Response statuses that are retried, e.g. `502,503`
+    #[arg(long, global = true, value_name = "STATUSES", value_delimiter = ',')]
+    pub retry_status: Vec<u16>,
+
+    /// Send the request through this http(s) or socks5 proxy instead of the environment proxy
+    #[arg(long = "proxy", global = true, value_name = "URL")]
+    pub proxy_url: Option<String>,
+
+    /// Client certificate for mutual TLS, overrides the environment TLS settings
+    #[arg(long, global = true, value_name = "FILE")]
+    pub cert: Option<String>,
+
+    /// PKCS#8 key of the client certificate
+    #[arg(long, global = true, value_name = "FILE")]
+    pub key: Option<String>,
+
+    /// Also trust the certificate authorities in this PEM bundle
+    #[arg(long, global = true, value_name = "FILE")]
+    pub cacert: Option<String>,
+
+    /// Accept invalid and self-signed server certificates
+    #[arg(short = 'k', long, global = true)]
+    pub insecure: bool,
+
+    /// Minimum TLS version: 1.0, 1.1, 1.2 or 1.3
+    #[arg(long, global = true, value_name = "VERSION")]
+    pub tls_min: Option<String>,
+
+    /// Send every line of the file as a frame after connecting a WEBSOCKET request
+    #[arg(long, global = true, value_name = "FILE")]
+    pub frames_file: Option<String>,
+
+    /// Milliseconds to wait for incoming WEBSOCKET messages before closing, defaults to 5000
+    #[arg(long, global = true, value_name = "MS")]
+    pub ws_timeout: Option<u64>,
+
+    /// Close the WEBSOCKET connection after receiving this many messages
+    #[arg(long, global = true, value_name = "COUNT")]
+    pub ws_messages: Option<usize>,
+
+    /// The .proto file describing the gRPC service of a PROTO request
+    #[arg(long, global = true, value_name = "FILE")]
+    pub proto_file: Option<String>,
+
+    /// Directory where proto imports are looked up, can be repeated
+    #[arg(long, global = true, value_name = "DIR")]
+    pub proto_include: Vec<String>,
+
+    /// The unary gRPC method to call, e.g. `package.Service/Method`
+    #[arg(long, global = true, value_name = "METHOD")]
+    pub grpc_method: Option<String>,
+}
+
+/// Parses a `key::value` pair, the value may contain `::` itself.
+fn key_value_pair(pair: &str) -> Result<(String, String), String> {
+    match pair.split_once("::") {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(String::from(
+            "expected key::value, e.g. `Accept::application/json`",
+        )),
     }
+}
+
+/// Flags of the first versions that are rewritten to their current long form.
+const LEGACY_FLAGS: [(&str, &str); 18] = [
+    ("-h=", "--header="),
+    ("-fd=", "--form-data="),
+    ("-bt=", "--body-type="),
+    ("-rf=", "--response-file="),
+    ("-ct=", "--connect-timeout="),
+    ("-to=", "--timeout="),
+    ("-mr=", "--max-redirects="),
+    ("-rt=", "--retries="),
+    ("-rb=", "--retry-backoff="),
+    ("-rs=", "--retry-status="),
+    ("-ff=", "--frames-file="),
+    ("-wt=", "--ws-timeout="),
+    ("-wm=", "--ws-messages="),
+    ("-pf=", "--proto-file="),
+    ("-pi=", "--proto-include="),
+    ("-gm=", "--grpc-method="),
+    ("-sec", "--secure"),
+    ("-nf", "--no-follow"),
+];
 
-    fn print_command(cmd: &str, description: &str) {
-        println!("{} => {}", cmd.green().bold(), description.blue().italic());
+/// Commands of the first versions given as one argument, with the subcommand they became and
+/// how many `::` separated values follow the `=`. Commands without values match exactly.
+const LEGACY_COMMANDS: [(&str, &[&str], usize); 39] = [
+    ("print-env-all", &["env", "list"], 0),
+    ("print-env", &["env", "show"], 0),
+    ("print-cookies", &["cookies", "show"], 0),
+    ("clear-cookies", &["cookies", "clear"], 0),
+    ("-r-auth", &["auth", "remove"], 0),
+    ("--remove-authorization", &["auth", "remove"], 0),
+    ("-r-tls", &["tls", "remove"], 0),
+    ("--remove-tls", &["tls", "remove"], 0),
+    ("-r-px", &["proxy", "remove"], 0),
+    ("--remove-proxy", &["proxy", "remove"], 0),
+    ("test=", &["test"], 1),
+    ("run=", &["run"], 1),
+    ("import-postman=", &["import", "postman"], 1),
+    ("import-curl=", &["import", "curl"], 1),
+    ("export-curl=", &["export", "curl"], 1),
+    ("completions=", &["completions"], 1),
+    ("-c=", &["call"], 1),
+    ("--call=", &["call"], 1),
+    ("-s=", &["request", "save"], 1),
+    ("--save=", &["request", "save"], 1),
+    ("-sc=", &["request", "save-and-call"], 1),
+    ("--save-and-call=", &["request", "save-and-call"], 1),
+    ("-d=", &["request", "delete"], 1),
+    ("--delete=", &["request", "delete"], 1),
+    ("-se=", &["env", "select"], 1),
+    ("--select-env=", &["env", "select"], 1),
+    ("-ed=", &["env", "delete"], 1),
+    ("--env-delete=", &["env", "delete"], 1),
+    ("-edv=", &["env", "unset"], 2),
+    ("-env-delete-value=", &["env", "unset"], 2),
+    ("--env-delete-value=", &["env", "unset"], 2),
+    ("-e=", &["env", "set"], 3),
+    ("--env=", &["env", "set"], 3),
+    ("-auth=", &["auth", "set"], 1),
+    ("--authorization=", &["auth", "set"], 1),
+    ("-tls=", &["tls", "set"], 2),
+    ("--tls=", &["tls", "set"], 2),
+    ("-px=", &["proxy", "set"], 2),
+    ("--set-proxy=", &["proxy", "set"], 2),
+];
+
+/// Rewrites a legacy command argument to its subcommand arguments.
+fn legacy_command(arg: &str) -> Option<Vec<String>> {
+    LEGACY_COMMANDS
+        .iter()
+        .find_map(|(legacy, subcommand, count)| {
+            let values = match count {
+                0 => (arg == *legacy).then(Vec::new)?,
+                _ => arg.strip_prefix(legacy)?.splitn(*count, "::").collect(),
+            };
+            Some(
+                subcommand
+                    .iter()
+                    .chain(&values)
+                    .map(|value| value.to_string())
+                    .collect(),
+            )
+        })
+}
+
+impl GemonCli {
+    /// The `--project` path of the arguments. It is read before they are parsed, since the
+    /// environment values filled into them come from that project.
+    pub fn project_path(args: &[String]) -> Option<PathBuf> {
+        GemonCli::command()
+            .ignore_errors(true)
+            .try_get_matches_from(GemonCli::translate_legacy(args.to_vec()))
+            .ok()?
+            .get_one::<PathBuf>("project")
+            .cloned()
     }
 
-    fn print_info(info: &str) {
-        println!("{}", info.yellow().italic());
+    /// Rewrites the `-x=value` arguments of the first versions into the current flags and
+    /// subcommands, the subcommand is moved in front of the options. Nothing after a current
+    /// subcommand is rewritten, so values such as `env set dev mode print-env` are kept.
+    pub fn translate_legacy(args: Vec<String>) -> Vec<String> {
+        // Options taking the next argument as their value, that argument is never rewritten
+        let command = GemonCli::command();
+        let takes_value: HashSet<String> = command
+            .get_arguments()
+            .filter(|arg| arg.get_action().takes_values() && !arg.is_require_equals_set())
+            .flat_map(|arg| {
+                arg.get_long()
+                    .map(|long| format!("--{long}"))
+                    .into_iter()
+                    .chain(arg.get_short().map(|short| format!("-{short}")))
+            })
+            .collect();
+        let subcommands: HashSet<&str> = command
+            .get_subcommands()
+            .flat_map(|subcommand| subcommand.get_name_and_visible_aliases())
+            .collect();
+
+        let mut args = args.into_iter();
+        let mut program = args.next().into_iter().collect::<Vec<_>>();
+        let mut subcommand = Vec::new();
+        let mut options = Vec::new();
+        let mut is_value = false;
+        while let Some(arg) = args.next() {
+            if is_value {
+                is_value = false;
+                options.push(arg);
+                continue;
+            }
+            if subcommands.contains(arg.as_str()) {
+                options.push(arg);
+                options.extend(args);
+                break;
+            }
+            is_value = takes_value.contains(&arg);
+            match legacy_command(&arg) {
+                Some(legacy) => subcommand.extend(legacy),
+                None => options.push(
+                    LEGACY_FLAGS
+                        .iter()
+                        .find_map(|(legacy, long)| {
+                            arg.strip_prefix(legacy).map(|rest| format!("{long}{rest}"))
+                        })
+                        .unwrap_or(arg),
+                ),
+            }
+        }
+        program.extend(subcommand);
+        program.extend(options);
+        program
     }
 }
@@ -0,0 +1,114 @@
+use crate::command::GemonCli;
+use crate::project::project_handler::{get_project, list_saved_requests};
+use clap::{CommandFactory, ValueEnum};
+use clap_complete::{
+    env::{Bash, EnvCompleter, Fish, Zsh},
+    CompleteEnv, CompletionCandidate,
+};
+use std::io::{self, Write};
+
+/// Environment variable the completion scripts call gemon with, it makes gemon print the
+/// completions of the arguments after `--` instead of running them.
+const COMPLETE_VAR: &str = "GEMON_COMPLETE";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl CompletionShell {
+    fn completer(&self) -> &'static dyn EnvCompleter {
+        match self {
+            CompletionShell::Bash => &Bash,
+            CompletionShell::Zsh => &Zsh,
+            CompletionShell::Fish => &Fish,
+        }
+    }
+}
+
+/// The command line being completed when the shell scripts called gemon, `args` otherwise.
+pub fn command_line(args: &[String]) -> &[String] {
+    let completing = std::env::var_os(COMPLETE_VAR).is_some_and(|shell| !shell.is_empty());
+    match args.iter().position(|arg| arg == "--") {
+        Some(separator) if completing => &args[separator + 1..],
+        _ => args,
+    }
+}
+
+/// Answers a completion request of the shell scripts and exits, does nothing on a regular run.
+pub fn complete_from_env() {
+    CompleteEnv::with_factory(GemonCli::command)
+        .var(COMPLETE_VAR)
+        .complete();
+}
+
+/// Writes the script registering gemon completions in the shell. The script asks gemon itself
+/// for the flags, commands and values, so saved requests and environments are always current.
+pub fn write_script(shell: CompletionShell, buf: &mut dyn Write) -> Result<(), io::Error> {
+    shell
+        .completer()
+        .write_registration(COMPLETE_VAR, "gemon", "gemon", "gemon", buf)
+}
+
+/// Names of the requests saved in the project of the current folder.
+pub fn saved_requests() -> Vec<CompletionCandidate> {
+    list_saved_requests()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|request| {
+            CompletionCandidate::new(request.name).help(Some(request.request_type.into()))
+        })
+        .collect()
+}
+
+/// Names of the environments of the project in the current folder.
+pub fn environments() -> Vec<CompletionCandidate> {
+    let mut names = get_project()
+        .map(|project| project.environments().keys().cloned().collect::<Vec<_>>())
+        .unwrap_or_default();
+    names.sort();
+    names.into_iter().map(CompletionCandidate::new).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{write_script, CompletionShell};
+    use crate::command::GemonCli;
+    use clap::CommandFactory;
+    use clap_complete::engine::complete;
+
+    fn candidates(args: &[&str]) -> Vec<String> {
+        let args = args.iter().map(Into::into).collect::<Vec<_>>();
+        let index = args.len() - 1;
+        complete(&mut GemonCli::command(), args, index, None)
+            .unwrap()
+            .into_iter()
+            .map(|candidate| candidate.get_value().to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn completions_cover_commands_flags_and_values() {
+        for shell in [
+            CompletionShell::Bash,
+            CompletionShell::Zsh,
+            CompletionShell::Fish,
+        ] {
+            let mut script = Vec::new();
+            write_script(shell, &mut script).unwrap();
+            let script = String::from_utf8(script).unwrap();
+            assert!(script.contains("GEMON_COMPLETE") && script.contains("gemon"));
+        }
+
+        assert!(candidates(&["gemon", "en"]).contains(&String::from("env")));
+        assert_eq!(candidates(&["gemon", "env", "se"]), ["select", "set"]);
+        assert_eq!(
+            candidates(&["gemon", "call", "login", "--retry-b"]),
+            ["--retry-backoff"]
+        );
+        assert_eq!(candidates(&["gemon", "-t", "W"]), ["WEBSOCKET"]);
+        assert_eq!(candidates(&["gemon", "completions", "z"]), ["zsh"]);
+    }
+}
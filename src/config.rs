@@ -1,10 +1,23 @@
 use chrono::Local;
 
-use crate::config::{
-    arguments::{GemonArgument, GemonArguments},
-    types::{GemonMethodType, GemonPrinter, GemonScenario, GemonType},
+use crate::{
+    config::{
+        arguments::{GemonArgument, GemonArguments},
+        effector::Effector,
+        types::{
+            GemonBodyType, GemonMethodType, GemonOutputDetail, GemonPrinter, GemonScenario,
+            GemonStatusRanges, GemonType,
+        },
+    },
+    printer::filter::Filter,
+    project::project_handler::project_root,
+    request::{rest_request::RequestOptions, tls::TlsSettings},
+};
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Read},
 };
-use std::{collections::HashMap, io};
 
 use self::types::GemonProjectScenario;
 
@@ -13,6 +26,12 @@ pub mod effector;
 pub mod parser;
 pub mod types;
 
+/// Where the body is read from when it is not passed with `-b=`.
+enum BodySource {
+    File(String),
+    Stdin,
+}
+
 pub struct GemonConfigBuilder {
     gemon_scenario: GemonScenario,
     gemon_type: GemonType,
@@ -20,11 +39,26 @@ pub struct GemonConfigBuilder {
     url: Option<String>,
     headers: HashMap<String, String>,
     body: Option<String>,
+    body_source: Option<BodySource>,
+    binary_body: Option<Vec<u8>>,
+    body_type: Option<GemonBodyType>,
     form_data: HashMap<String, String>,
+    frames_file: Option<String>,
+    websocket_timeout_ms: Option<u64>,
+    websocket_max_messages: Option<usize>,
+    proto_file: Option<String>,
+    proto_import_paths: Vec<String>,
+    grpc_method: Option<String>,
+    request_options: RequestOptions,
+    tls: TlsSettings,
+    proxy: Option<String>,
     write_to_request_response_file: bool,
     response_file_path: Option<String>,
     log_response: bool,
     also_print_to_terminal: bool,
+    output_detail: GemonOutputDetail,
+    fail_statuses: Option<GemonStatusRanges>,
+    filter: Option<Filter>,
     secure: bool,
 }
 
@@ -37,11 +71,26 @@ impl GemonConfigBuilder {
             url: None,
             headers: HashMap::new(),
             body: None,
+            body_source: None,
+            binary_body: None,
+            body_type: None,
             form_data: HashMap::new(),
+            frames_file: None,
+            websocket_timeout_ms: None,
+            websocket_max_messages: None,
+            proto_file: None,
+            proto_import_paths: Vec::new(),
+            grpc_method: None,
+            request_options: RequestOptions::default(),
+            tls: TlsSettings::default(),
+            proxy: None,
             response_file_path: None,
             write_to_request_response_file: false,
             log_response: false,
             also_print_to_terminal: false,
+            output_detail: GemonOutputDetail::Body,
+            fail_statuses: None,
+            filter: None,
             secure: false,
         }
     }
@@ -51,15 +100,39 @@ impl GemonConfigBuilder {
             GemonArgument::Type(t) => self.gemon_type = *t,
             GemonArgument::Method {
                 gemon_method_type: t,
-            } => self.gemon_method_type = Some(*t),
+            } => self.gemon_method_type = Some(t.clone()),
             GemonArgument::Uri(t) => self.url = Some(t.to_string()),
             GemonArgument::Header(key, value) => {
                 self.headers.insert(key.into(), value.into());
             }
-            GemonArgument::Body(b) => self.body = Some(b.to_string()),
+            GemonArgument::Body(b) => {
+                self.body = Some(b.to_string());
+                self.body_source = None;
+            }
+            GemonArgument::BodyFile(f) => self.body_source = Some(BodySource::File(f.to_string())),
+            GemonArgument::BodyStdin => self.body_source = Some(BodySource::Stdin),
+            GemonArgument::BodyType(t) => self.body_type = Some(*t),
             GemonArgument::FormData(key, value) => {
                 self.form_data.insert(key.into(), value.into());
             }
+            GemonArgument::FramesFile(f) => self.frames_file = Some(f.to_string()),
+            GemonArgument::WebsocketTimeout(t) => self.websocket_timeout_ms = Some(*t),
+            GemonArgument::WebsocketMaxMessages(m) => self.websocket_max_messages = Some(*m),
+            GemonArgument::ProtoFile(f) => self.proto_file = Some(f.to_string()),
+            GemonArgument::ProtoInclude(i) => self.proto_import_paths.push(i.to_string()),
+            GemonArgument::GrpcMethod(m) => self.grpc_method = Some(m.to_string()),
+            GemonArgument::ConnectTimeout(t) => self.request_options.connect_timeout_ms = Some(*t),
+            GemonArgument::Timeout(t) => self.request_options.timeout_ms = Some(*t),
+            GemonArgument::MaxRedirects(m) => self.request_options.max_redirects = Some(*m),
+            GemonArgument::Retries(r) => self.request_options.retries = Some(*r),
+            GemonArgument::RetryBackoff(b) => self.request_options.retry_backoff_ms = Some(*b),
+            GemonArgument::RetryStatuses(s) => self.request_options.retry_statuses = s.clone(),
+            GemonArgument::Proxy(p) => self.proxy = Some(p.to_string()),
+            GemonArgument::TlsCert(c) => self.tls.cert = Some(c.to_string()),
+            GemonArgument::TlsKey(k) => self.tls.key = Some(k.to_string()),
+            GemonArgument::TlsCaCert(c) => self.tls.ca_cert = Some(c.to_string()),
+            GemonArgument::TlsMinVersion(v) => self.tls.min_version = Some(v.to_string()),
+            GemonArgument::TlsInsecure => self.tls.insecure = Some(true),
             GemonArgument::ResponseFilePath(f) => match f {
                 Some(path) => self.response_file_path = Some(path.to_owned()),
                 None => self.write_to_request_response_file = true,
@@ -69,6 +142,11 @@ impl GemonConfigBuilder {
             }
             GemonArgument::LogResponse => self.log_response = true,
             GemonArgument::AlsoPrintToTerminal => self.also_print_to_terminal = true,
+            GemonArgument::Filter(filter) => self.filter = Some(filter.clone()),
+            GemonArgument::FailStatuses(statuses) => self.fail_statuses = Some(statuses.clone()),
+            GemonArgument::OutputDetail(detail) => {
+                self.output_detail = self.output_detail.max(*detail)
+            }
             GemonArgument::MiscScenario(scenario) => {
                 self.gemon_scenario = GemonScenario::Misc(scenario.clone())
             }
@@ -76,28 +154,56 @@ impl GemonConfigBuilder {
         }
     }
 
+    /// Reads the body from its file or the standard input. Text gets the environment values
+    /// filled in like `-b=` does, anything that is not UTF-8 becomes a binary body.
+    fn read_body(&mut self) -> Result<(), io::Error> {
+        let bytes = match self.body_source.take() {
+            Some(BodySource::File(path)) => fs::read(&path).map_err(|err| {
+                io::Error::new(
+                    err.kind(),
+                    format!("Could not read body file {path}: {err}"),
+                )
+            })?,
+            Some(BodySource::Stdin) => {
+                let mut bytes = Vec::new();
+                io::stdin().read_to_end(&mut bytes)?;
+                bytes
+            }
+            None => return Ok(()),
+        };
+        match String::from_utf8(bytes) {
+            Ok(text) => self.body = Some(Effector::apply_env_to_string(text)),
+            Err(err) => {
+                self.body = None;
+                self.binary_body = Some(err.into_bytes());
+                self.body_type.get_or_insert(GemonBodyType::Binary);
+            }
+        }
+        Ok(())
+    }
+
     fn build_response_file_path(
         write_to_request_response_file: bool,
         log_response: bool,
         response_file_path: &Option<String>,
         gemon_scenario: &GemonScenario,
     ) -> Option<String> {
-        match write_to_request_response_file {
+        let file = match write_to_request_response_file {
             true => match gemon_scenario {
                 GemonScenario::Project(GemonProjectScenario::Call(name)) if log_response => {
                     let now = Local::now();
-                    Some(format!(
-                        "{name}/response_{}.json",
-                        now.format("%Y_%m_%d_%H_%M_%S")
-                    ))
+                    format!("{name}/response_{}.json", now.format("%Y_%m_%d_%H_%M_%S"))
                 }
                 GemonScenario::Project(GemonProjectScenario::Call(name)) if !log_response => {
-                    Some(format!("{name}/response.json"))
+                    format!("{name}/response.json")
                 }
-                _ => None,
+                _ => return None,
             },
-            false => response_file_path.to_owned(),
-        }
+            false => return response_file_path.to_owned(),
+        };
+        // Saved requests live in the project folder, which may be above the current one
+        let path = project_root().unwrap_or_default().join(file);
+        Some(path.to_string_lossy().into_owned())
     }
 
     fn build(self) -> GemonConfig {
@@ -119,9 +225,23 @@ impl GemonConfigBuilder {
             url: self.url,
             headers: self.headers,
             body: self.body,
+            binary_body: self.binary_body,
+            body_type: self.body_type,
             form_data: self.form_data,
+            frames_file: self.frames_file,
+            websocket_timeout_ms: self.websocket_timeout_ms,
+            websocket_max_messages: self.websocket_max_messages,
+            proto_file: self.proto_file,
+            proto_import_paths: self.proto_import_paths,
+            grpc_method: self.grpc_method,
+            request_options: self.request_options,
+            tls: self.tls,
+            proxy: self.proxy,
             response_file_path: path,
             also_print_to_terminal: self.also_print_to_terminal,
+            output_detail: self.output_detail,
+            fail_statuses: self.fail_statuses,
+            filter: self.filter,
             secure: self.secure,
         }
     }
@@ -136,9 +256,23 @@ pub struct GemonConfig {
     url: Option<String>,
     headers: HashMap<String, String>,
     body: Option<String>,
+    binary_body: Option<Vec<u8>>,
+    body_type: Option<GemonBodyType>,
     form_data: HashMap<String, String>,
+    frames_file: Option<String>,
+    websocket_timeout_ms: Option<u64>,
+    websocket_max_messages: Option<usize>,
+    proto_file: Option<String>,
+    proto_import_paths: Vec<String>,
+    grpc_method: Option<String>,
+    request_options: RequestOptions,
+    tls: TlsSettings,
+    proxy: Option<String>,
     response_file_path: Option<String>,
     also_print_to_terminal: bool,
+    output_detail: GemonOutputDetail,
+    fail_statuses: Option<GemonStatusRanges>,
+    filter: Option<Filter>,
     secure: bool,
 }
 
@@ -159,13 +293,75 @@ impl GemonConfig {
             url: Some(url),
             headers,
             body,
+            binary_body: None,
+            body_type: None,
             form_data,
+            frames_file: None,
+            websocket_timeout_ms: None,
+            websocket_max_messages: None,
+            proto_file: None,
+            proto_import_paths: Vec::new(),
+            grpc_method: None,
+            request_options: RequestOptions::default(),
+            tls: TlsSettings::default(),
+            proxy: None,
             response_file_path: None,
             also_print_to_terminal: false,
+            output_detail: GemonOutputDetail::Body,
+            fail_statuses: None,
+            filter: None,
             secure,
         }
     }
 
+    pub fn with_binary_body(self, binary_body: Option<Vec<u8>>) -> GemonConfig {
+        GemonConfig {
+            binary_body,
+            ..self
+        }
+    }
+
+    pub fn with_body_type(self, body_type: GemonBodyType) -> GemonConfig {
+        GemonConfig {
+            body_type: Some(body_type),
+            ..self
+        }
+    }
+
+    pub fn with_request_options(self, request_options: RequestOptions) -> GemonConfig {
+        GemonConfig {
+            request_options,
+            ..self
+        }
+    }
+
+    pub fn websocket_request(
+        url: String,
+        headers: HashMap<String, String>,
+        body: Option<String>,
+        frames_file: Option<String>,
+        websocket_timeout_ms: Option<u64>,
+        websocket_max_messages: Option<usize>,
+        secure: bool,
+    ) -> GemonConfig {
+        GemonConfig {
+            gemon_type: GemonType::Websocket,
+            gemon_method_type: None,
+            form_data: HashMap::new(),
+            frames_file,
+            websocket_timeout_ms,
+            websocket_max_messages,
+            ..GemonConfig::rest_request(
+                GemonMethodType::Get,
+                url,
+                headers,
+                body,
+                HashMap::new(),
+                secure,
+            )
+        }
+    }
+
     pub fn new(gemon_arguments: &GemonArguments) -> Result<GemonConfig, io::Error> {
         let mut builder = GemonConfigBuilder::new();
 
@@ -173,6 +369,7 @@ impl GemonConfig {
             .arguments()
             .iter()
             .for_each(|argument| builder.process_argument(argument));
+        builder.read_body()?;
 
         let config = builder.build();
         Ok(config)
@@ -191,7 +388,9 @@ impl GemonConfig {
     }
 
     pub fn gemon_method_type(&self) -> GemonMethodType {
-        self.gemon_method_type.unwrap_or(GemonMethodType::Get)
+        self.gemon_method_type
+            .clone()
+            .unwrap_or(GemonMethodType::Get)
     }
 
     pub fn gemon_url(&self) -> String {
@@ -206,10 +405,56 @@ impl GemonConfig {
         self.body.to_owned()
     }
 
+    /// Body read from a file or the standard input that is not UTF-8 text.
+    pub fn gemon_binary_body(&self) -> Option<&[u8]> {
+        self.binary_body.as_deref()
+    }
+
+    /// Body type passed on the command line, `None` lets the request infer it.
+    pub fn gemon_body_type(&self) -> Option<GemonBodyType> {
+        self.body_type
+    }
+
     pub fn gemon_form_data(&self) -> &HashMap<String, String> {
         &self.form_data
     }
 
+    pub fn gemon_frames_file(&self) -> Option<String> {
+        self.frames_file.to_owned()
+    }
+
+    pub fn gemon_websocket_timeout_ms(&self) -> Option<u64> {
+        self.websocket_timeout_ms
+    }
+
+    pub fn gemon_websocket_max_messages(&self) -> Option<usize> {
+        self.websocket_max_messages
+    }
+
+    pub fn gemon_proto_file(&self) -> Option<String> {
+        self.proto_file.to_owned()
+    }
+
+    pub fn gemon_proto_import_paths(&self) -> &[String] {
+        &self.proto_import_paths
+    }
+
+    pub fn gemon_grpc_method(&self) -> Option<String> {
+        self.grpc_method.to_owned()
+    }
+
+    pub fn gemon_request_options(&self) -> &RequestOptions {
+        &self.request_options
+    }
+
+    pub fn gemon_tls(&self) -> &TlsSettings {
+        &self.tls
+    }
+
+    pub fn gemon_proxy(&self) -> Option<String> {
+        self.proxy.to_owned()
+    }
+
     pub fn gemon_response_file_path(&self) -> Option<String> {
         self.response_file_path.to_owned()
     }
@@ -218,6 +463,19 @@ impl GemonConfig {
         self.also_print_to_terminal
     }
 
+    pub fn gemon_output_detail(&self) -> GemonOutputDetail {
+        self.output_detail
+    }
+
+    pub fn gemon_filter(&self) -> Option<&Filter> {
+        self.filter.as_ref()
+    }
+
+    /// Response statuses the call fails on, `None` when any response is a success.
+    pub fn gemon_fail_statuses(&self) -> Option<&GemonStatusRanges> {
+        self.fail_statuses.as_ref()
+    }
+
     pub fn gemon_secure(&self) -> bool {
         self.secure
     }
@@ -1,6 +1,10 @@
-use crate::config::parser::GemonArgumentParser;
-use crate::config::types::{GemonMethodType, GemonType};
-use std::io;
+use crate::command::GemonCli;
+use crate::config::types::{
+    GemonBodyType, GemonMethodType, GemonOutputDetail, GemonStatusRanges, GemonType,
+};
+use crate::printer::{filter::Filter, highlight};
+use clap::{error::ErrorKind, CommandFactory, Parser};
+use std::{error::Error, fmt};
 
 use super::types::{GemonProjectScenario, MiscScenario};
 
@@ -11,21 +15,71 @@ pub enum GemonArgument {
     Uri(String),
     Header(String, String),
     Body(String),
+    BodyFile(String),
+    BodyStdin,
+    BodyType(GemonBodyType),
     FormData(String, String),
+    FramesFile(String),
+    WebsocketTimeout(u64),
+    WebsocketMaxMessages(usize),
+    ProtoFile(String),
+    ProtoInclude(String),
+    GrpcMethod(String),
+    ConnectTimeout(u64),
+    Timeout(u64),
+    MaxRedirects(usize),
+    Retries(u32),
+    RetryBackoff(u64),
+    RetryStatuses(Vec<u16>),
+    Proxy(String),
+    TlsCert(String),
+    TlsKey(String),
+    TlsCaCert(String),
+    TlsMinVersion(String),
+    TlsInsecure,
     ResponseFilePath(Option<String>),
     ProjectSetup(GemonProjectScenario),
     MiscScenario(MiscScenario),
     LogResponse,
     AlsoPrintToTerminal,
+    OutputDetail(GemonOutputDetail),
+    FailStatuses(GemonStatusRanges),
+    Filter(Filter),
     Secure,
 }
 
 impl Default for GemonArgument {
     fn default() -> Self {
-        GemonArgument::ProjectSetup(GemonProjectScenario::Help)
+        GemonArgument::MiscScenario(MiscScenario::Help(render_help(
+            GemonCli::command().render_long_help(),
+        )))
     }
 }
 
+/// Help text with its styles when the terminal output is colored.
+fn render_help(help: clap::builder::StyledStr) -> String {
+    match highlight::colors_enabled() {
+        true => help.ansi().to_string(),
+        false => help.to_string(),
+    }
+}
+
+/// Invalid command line arguments: an unknown flag or subcommand, a missing or a badly formed
+/// value. The message tells what was expected and suggests similar flags.
+#[derive(Debug)]
+pub struct ArgumentError {
+    error: clap::Error,
+}
+
+impl fmt::Display for ArgumentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = self.error.render().to_string();
+        write!(f, "{}", message.trim_start_matches("error: ").trim_end())
+    }
+}
+
+impl Error for ArgumentError {}
+
 #[derive(Debug)]
 pub struct GemonArguments {
     arguments: Vec<GemonArgument>,
@@ -40,21 +94,22 @@ impl Default for GemonArguments {
 }
 
 impl GemonArguments {
-    pub fn new(input_args: Vec<String>) -> Result<GemonArguments, io::Error> {
+    pub fn new(input_args: Vec<String>) -> Result<GemonArguments, ArgumentError> {
         if input_args.len() < 2 {
             return Ok(GemonArguments::default());
         }
 
-        let arguments = input_args
-            .into_iter()
-            .map(|arg| arg.parse_argument())
-            .filter(|arg| !arg.is_none())
-            .map(|arg| arg.unwrap())
-            .collect();
-
-        let gemon_arguments = GemonArguments { arguments };
+        let arguments = match GemonCli::try_parse_from(GemonCli::translate_legacy(input_args)) {
+            Ok(cli) => cli.into_arguments(),
+            Err(error) if error.kind() == ErrorKind::DisplayHelp => {
+                vec![GemonArgument::MiscScenario(MiscScenario::Help(
+                    render_help(error.render()),
+                ))]
+            }
+            Err(error) => return Err(ArgumentError { error }),
+        };
 
-        Ok(gemon_arguments)
+        Ok(GemonArguments { arguments })
     }
 
     pub fn arguments(&self) -> &Vec<GemonArgument> {
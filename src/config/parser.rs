@@ -1,188 +1,336 @@
-use crate::command::{Form, GemonCommand};
+use crate::command::{
+    AuthCommand, CookiesCommand, EnvCommand, ExportCommand, GemonCli, GemonSubcommand,
+    ImportCommand, RequestCommand, RequestOptions, SettingCommand,
+};
 use crate::config::arguments::GemonArgument;
-use crate::config::types::{GemonMethodType, GemonProjectScenario, GemonType};
+use crate::config::types::{GemonOutputDetail, GemonProjectScenario, GemonStatusRanges};
 
 use super::types::MiscScenario;
 
-fn simple_arg_parser(s: &str, i: usize) -> String {
-    let arg = &s[i..];
-    arg.to_string()
+impl GemonSubcommand {
+    fn into_argument(self) -> GemonArgument {
+        let scenario = match self {
+            GemonSubcommand::Init => GemonProjectScenario::Init,
+            GemonSubcommand::Tui => return GemonArgument::MiscScenario(MiscScenario::Tui),
+            GemonSubcommand::Completions { shell } => {
+                return GemonArgument::MiscScenario(MiscScenario::Completions(shell))
+            }
+            GemonSubcommand::Call { name } => GemonProjectScenario::Call(name),
+            GemonSubcommand::Print => GemonProjectScenario::PrintLastCall,
+            GemonSubcommand::Test { names } => GemonProjectScenario::Test(
+                names
+                    .into_iter()
+                    .map(|name| name.trim().to_string())
+                    .filter(|name| !name.is_empty())
+                    .collect(),
+            ),
+            GemonSubcommand::Run { name } => GemonProjectScenario::Run(name),
+            GemonSubcommand::Request(command) => match command {
+                RequestCommand::Save { name } => GemonProjectScenario::Save(name),
+                RequestCommand::SaveAndCall { name } => GemonProjectScenario::SaveAndCall(name),
+                RequestCommand::Delete { name } => GemonProjectScenario::Delete(name),
+            },
+            GemonSubcommand::Env(command) => match command {
+                EnvCommand::List => GemonProjectScenario::PrintEnvAll,
+                EnvCommand::Show => GemonProjectScenario::PrintEnv,
+                EnvCommand::Select { env } => GemonProjectScenario::SelectEnv(env),
+                EnvCommand::Set { env, key, value } => {
+                    GemonProjectScenario::AddEnv(env, key, value)
+                }
+                EnvCommand::Unset { env, key } => GemonProjectScenario::RemoveEnvValue(env, key),
+                EnvCommand::Delete { env } => GemonProjectScenario::RemoveEnv(env),
+            },
+            GemonSubcommand::Auth(command) => match command {
+                AuthCommand::Set { value } => GemonProjectScenario::AddAuthorization(value),
+                AuthCommand::Remove => GemonProjectScenario::RemoveAuthorization,
+            },
+            GemonSubcommand::Tls(command) => match command {
+                SettingCommand::Set { setting, value } => {
+                    GemonProjectScenario::AddTlsSetting(setting, value)
+                }
+                SettingCommand::Remove => GemonProjectScenario::RemoveTls,
+            },
+            GemonSubcommand::Proxy(command) => match command {
+                SettingCommand::Set { setting, value } => {
+                    GemonProjectScenario::AddProxySetting(setting, value)
+                }
+                SettingCommand::Remove => GemonProjectScenario::RemoveProxy,
+            },
+            GemonSubcommand::Cookies(command) => match command {
+                CookiesCommand::Show => GemonProjectScenario::PrintCookies,
+                CookiesCommand::Clear => GemonProjectScenario::ClearCookies,
+            },
+            GemonSubcommand::Import(command) => match command {
+                ImportCommand::Postman { path } => GemonProjectScenario::ImportPostman(path),
+                ImportCommand::Curl { name } => GemonProjectScenario::ImportCurl(name),
+            },
+            GemonSubcommand::Export(command) => match command {
+                ExportCommand::Curl { name } => GemonProjectScenario::ExportCurl(name),
+            },
+        };
+        GemonArgument::ProjectSetup(scenario)
+    }
 }
 
-fn key_value_pair_arg_parser(s: &str, i: usize) -> (String, String) {
-    let key_value = &s[i..];
-    let arg: Vec<&str> = key_value.split("::").collect();
-    let key = arg
-        .first()
-        .expect("arg key not provided correctly e.x `-h=key::value`")
-        .to_string();
-    let value = arg
-        .get(1)
-        .expect("arg value not provided correctly e.x `-h=key::value`")
-        .to_string();
-    (key, value)
+impl RequestOptions {
+    fn into_arguments(self) -> Vec<GemonArgument> {
+        let mut arguments = Vec::new();
+        arguments.extend(self.gemon_type.map(GemonArgument::Type));
+        arguments.extend(
+            self.method
+                .map(|gemon_method_type| GemonArgument::Method { gemon_method_type }),
+        );
+        arguments.extend(self.uri.map(GemonArgument::Uri));
+        arguments.extend(
+            self.headers
+                .into_iter()
+                .map(|(key, value)| GemonArgument::Header(key, value)),
+        );
+        arguments.extend(self.body.map(GemonArgument::Body));
+        arguments.extend(self.body_file.map(GemonArgument::BodyFile));
+        if self.body_stdin {
+            arguments.push(GemonArgument::BodyStdin);
+        }
+        arguments.extend(self.body_type.map(GemonArgument::BodyType));
+        arguments.extend(
+            self.form_data
+                .into_iter()
+                .map(|(key, value)| GemonArgument::FormData(key, value)),
+        );
+        if self.file {
+            arguments.push(GemonArgument::ResponseFilePath(None));
+        }
+        if self.log {
+            arguments.push(GemonArgument::LogResponse);
+        }
+        if self.print {
+            arguments.push(GemonArgument::AlsoPrintToTerminal);
+        }
+        arguments.extend(
+            self.response_file
+                .map(|path| GemonArgument::ResponseFilePath(Some(path))),
+        );
+        if self.include {
+            arguments.push(GemonArgument::OutputDetail(GemonOutputDetail::Include));
+        }
+        if self.verbose {
+            arguments.push(GemonArgument::OutputDetail(GemonOutputDetail::Verbose));
+        }
+        arguments.extend(self.filter.map(GemonArgument::Filter));
+        arguments.extend(self.fail.map(|statuses| {
+            GemonArgument::FailStatuses(statuses.unwrap_or_else(GemonStatusRanges::errors))
+        }));
+        if self.secure {
+            arguments.push(GemonArgument::Secure);
+        }
+        arguments.extend(self.connect_timeout.map(GemonArgument::ConnectTimeout));
+        arguments.extend(self.timeout.map(GemonArgument::Timeout));
+        arguments.extend(self.max_redirects.map(GemonArgument::MaxRedirects));
+        if self.no_follow {
+            arguments.push(GemonArgument::MaxRedirects(0));
+        }
+        arguments.extend(self.retries.map(GemonArgument::Retries));
+        arguments.extend(self.retry_backoff.map(GemonArgument::RetryBackoff));
+        if !self.retry_status.is_empty() {
+            arguments.push(GemonArgument::RetryStatuses(self.retry_status));
+        }
+        arguments.extend(self.proxy_url.map(GemonArgument::Proxy));
+        arguments.extend(self.cert.map(GemonArgument::TlsCert));
+        arguments.extend(self.key.map(GemonArgument::TlsKey));
+        arguments.extend(self.cacert.map(GemonArgument::TlsCaCert));
+        if self.insecure {
+            arguments.push(GemonArgument::TlsInsecure);
+        }
+        arguments.extend(self.tls_min.map(GemonArgument::TlsMinVersion));
+        arguments.extend(self.frames_file.map(GemonArgument::FramesFile));
+        arguments.extend(self.ws_timeout.map(GemonArgument::WebsocketTimeout));
+        arguments.extend(self.ws_messages.map(GemonArgument::WebsocketMaxMessages));
+        arguments.extend(self.proto_file.map(GemonArgument::ProtoFile));
+        arguments.extend(
+            self.proto_include
+                .into_iter()
+                .map(GemonArgument::ProtoInclude),
+        );
+        arguments.extend(self.grpc_method.map(GemonArgument::GrpcMethod));
+        arguments
+    }
 }
 
-fn triple_value_arg_parser(s: &str, i: usize) -> (String, String, String) {
-    let group = &s[i..];
-    let arg: Vec<&str> = group.split("::").collect();
-    let one = arg
-        .first()
-        .expect("arg one not provided correctly for triple touple e.x `-[e]=one::two::three`")
-        .to_string();
-    let two = arg
-        .get(1)
-        .expect("arg two not provided correctly for triple touple e.x `-[e]=one::two::three`")
-        .to_string();
-    let three = arg
-        .get(2)
-        .expect("arg three not provided correctly for triple touple e.x `-[e]=one::two::three`")
-        .to_string();
-    (one, two, three)
+impl GemonCli {
+    /// The arguments the parsed command line stands for, the subcommand comes last so it decides
+    /// the scenario.
+    pub fn into_arguments(self) -> Vec<GemonArgument> {
+        if self.version {
+            return vec![GemonArgument::MiscScenario(MiscScenario::Version)];
+        }
+        let mut arguments = self.request.into_arguments();
+        arguments.extend(self.command.map(GemonSubcommand::into_argument));
+        arguments
+    }
 }
 
-pub trait GemonArgumentParser {
-    fn parse_argument(self) -> Option<GemonArgument>;
-}
+#[cfg(test)]
+mod tests {
+    use crate::command::GemonCli;
+    use crate::config::arguments::{GemonArgument, GemonArguments};
+    use crate::config::types::{GemonMethodType, GemonProjectScenario, GemonType};
+    use std::path::PathBuf;
 
-impl GemonArgumentParser for String {
-    fn parse_argument(self) -> Option<GemonArgument> {
-        let cmd: GemonCommand = self.into();
-        match cmd {
-            GemonCommand::Help => Some(GemonArgument::ProjectSetup(GemonProjectScenario::Help)),
-            GemonCommand::Version => Some(GemonArgument::MiscScenario(MiscScenario::Version)),
-            GemonCommand::Init => Some(GemonArgument::ProjectSetup(GemonProjectScenario::Init)),
-            GemonCommand::PrintEnvAll => Some(GemonArgument::ProjectSetup(
-                GemonProjectScenario::PrintEnvAll,
-            )),
-            GemonCommand::PrintEnv => {
-                Some(GemonArgument::ProjectSetup(GemonProjectScenario::PrintEnv))
-            }
-            GemonCommand::PrintLastCall => Some(GemonArgument::ProjectSetup(
-                GemonProjectScenario::PrintLastCall,
-            )),
-            GemonCommand::TypeRest => Some(GemonArgument::Type(GemonType::Rest)),
-            GemonCommand::TypeWebsocket => Some(GemonArgument::Type(GemonType::Websocket)),
-            GemonCommand::TypeProto => Some(GemonArgument::Type(GemonType::Proto)),
-            GemonCommand::MethodGet => Some(GemonArgument::Method {
-                gemon_method_type: GemonMethodType::Get,
-            }),
-            GemonCommand::MethodPost => Some(GemonArgument::Method {
-                gemon_method_type: GemonMethodType::Post,
-            }),
-            GemonCommand::MethodDelete => Some(GemonArgument::Method {
-                gemon_method_type: GemonMethodType::Delete,
-            }),
-            GemonCommand::MethodPut => Some(GemonArgument::Method {
-                gemon_method_type: GemonMethodType::Put,
-            }),
-            GemonCommand::MethodPatch => Some(GemonArgument::Method {
-                gemon_method_type: GemonMethodType::Patch,
-            }),
-            GemonCommand::File => Some(GemonArgument::ResponseFilePath(None)),
-            GemonCommand::LogResponse => Some(GemonArgument::LogResponse),
-            GemonCommand::AlsoPrintToTerminal => Some(GemonArgument::AlsoPrintToTerminal),
-            GemonCommand::Uri(s, Form::Short) => Some(GemonArgument::Uri(simple_arg_parser(&s, 3))),
-            GemonCommand::Uri(s, Form::Long) => Some(GemonArgument::Uri(simple_arg_parser(&s, 6))),
-            GemonCommand::Header(s, Form::Short) => {
-                let arg = key_value_pair_arg_parser(&s, 3);
-                Some(GemonArgument::Header(arg.0, arg.1))
-            }
-            GemonCommand::Header(s, Form::Long) => {
-                let arg = key_value_pair_arg_parser(&s, 9);
-                Some(GemonArgument::Header(arg.0, arg.1))
-            }
-            GemonCommand::Body(s, Form::Short) => {
-                Some(GemonArgument::Body(simple_arg_parser(&s, 3)))
-            }
-            GemonCommand::Body(s, Form::Long) => {
-                Some(GemonArgument::Body(simple_arg_parser(&s, 6)))
-            }
-            GemonCommand::FormData(s, Form::Short) => {
-                let arg = key_value_pair_arg_parser(&s, 4);
-                Some(GemonArgument::FormData(arg.0, arg.1))
-            }
-            GemonCommand::FormData(s, Form::Long) => {
-                let arg = key_value_pair_arg_parser(&s, 12);
-                Some(GemonArgument::FormData(arg.0, arg.1))
-            }
-            GemonCommand::ResponseFile(s, Form::Short) => Some(GemonArgument::ResponseFilePath(
-                Some(simple_arg_parser(&s, 4)),
-            )),
-            GemonCommand::ResponseFile(s, Form::Long) => Some(GemonArgument::ResponseFilePath(
-                Some(simple_arg_parser(&s, 16)),
-            )),
-            GemonCommand::Save(s, Form::Short) => Some(GemonArgument::ProjectSetup(
-                GemonProjectScenario::Save(simple_arg_parser(&s, 3)),
-            )),
-            GemonCommand::Save(s, Form::Long) => Some(GemonArgument::ProjectSetup(
-                GemonProjectScenario::Save(simple_arg_parser(&s, 7)),
-            )),
-            GemonCommand::Call(s, Form::Short) => Some(GemonArgument::ProjectSetup(
-                GemonProjectScenario::Call(simple_arg_parser(&s, 3)),
-            )),
-            GemonCommand::Call(s, Form::Long) => Some(GemonArgument::ProjectSetup(
-                GemonProjectScenario::Call(simple_arg_parser(&s, 7)),
-            )),
-            GemonCommand::SaveAndCall(s, Form::Short) => Some(GemonArgument::ProjectSetup(
-                GemonProjectScenario::SaveAndCall(simple_arg_parser(&s, 3)),
-            )),
-            GemonCommand::SaveAndCall(s, Form::Long) => Some(GemonArgument::ProjectSetup(
-                GemonProjectScenario::SaveAndCall(simple_arg_parser(&s, 7)),
-            )),
-            GemonCommand::Delete(s, Form::Short) => Some(GemonArgument::ProjectSetup(
-                GemonProjectScenario::Delete(simple_arg_parser(&s, 3)),
-            )),
-            GemonCommand::Delete(s, Form::Long) => Some(GemonArgument::ProjectSetup(
-                GemonProjectScenario::Delete(simple_arg_parser(&s, 9)),
-            )),
-            GemonCommand::RemoveEnv(s, Form::Short) => Some(GemonArgument::ProjectSetup(
-                GemonProjectScenario::RemoveEnv(simple_arg_parser(&s, 4)),
-            )),
-            GemonCommand::RemoveEnv(s, Form::Long) => Some(GemonArgument::ProjectSetup(
-                GemonProjectScenario::RemoveEnv(simple_arg_parser(&s, 13)),
-            )),
-            GemonCommand::AddEnv(s, Form::Short) => {
-                let (one, two, three) = triple_value_arg_parser(&s, 3);
-                Some(GemonArgument::ProjectSetup(GemonProjectScenario::AddEnv(
-                    one, two, three,
-                )))
-            }
-            GemonCommand::AddEnv(s, Form::Long) => {
-                let (one, two, three) = triple_value_arg_parser(&s, 6);
-                Some(GemonArgument::ProjectSetup(GemonProjectScenario::AddEnv(
-                    one, two, three,
-                )))
-            }
-            GemonCommand::RemoveEnvValue(s, Form::Short) => {
-                let (one, two) = key_value_pair_arg_parser(&s, 5);
-                Some(GemonArgument::ProjectSetup(
-                    GemonProjectScenario::RemoveEnvValue(one, two),
-                ))
-            }
-            GemonCommand::RemoveEnvValue(s, Form::Long) => {
-                let (one, two) = key_value_pair_arg_parser(&s, 18);
-                Some(GemonArgument::ProjectSetup(
-                    GemonProjectScenario::RemoveEnvValue(one, two),
-                ))
+    fn parse(args: &[&str]) -> GemonArguments {
+        let args = std::iter::once("gemon")
+            .chain(args.iter().copied())
+            .map(String::from)
+            .collect();
+        GemonArguments::new(args).unwrap()
+    }
+
+    fn arguments(args: &[&str]) -> String {
+        format!("{:?}", parse(args).arguments())
+    }
+
+    fn error(args: &[&str]) -> String {
+        let args = std::iter::once("gemon")
+            .chain(args.iter().copied())
+            .map(String::from)
+            .collect();
+        GemonArguments::new(args).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn subcommands_and_legacy_forms_parse_to_the_same_arguments() {
+        let current = parse(&[
+            "request",
+            "save",
+            "login",
+            "-t",
+            "rest",
+            "-m",
+            "POST",
+            "--uri",
+            "{base}/login",
+            "-H",
+            "Accept::a::b",
+            "--retry-status=502,503",
+            "--fail",
+        ]);
+        let current = current.arguments();
+        let legacy = arguments(&[
+            "-t=REST",
+            "-m=POST",
+            "-u={base}/login",
+            "-h=Accept::a::b",
+            "-rs=502,503",
+            "--fail",
+            "-s=login",
+        ]);
+        assert_eq!(format!("{current:?}"), legacy);
+        assert!(matches!(current[0], GemonArgument::Type(GemonType::Rest)));
+        assert!(matches!(
+            &current[1],
+            GemonArgument::Method {
+                gemon_method_type: GemonMethodType::Post
             }
-            GemonCommand::SelectEnv(s, Form::Short) => Some(GemonArgument::ProjectSetup(
-                GemonProjectScenario::SelectEnv(simple_arg_parser(&s, 4)),
-            )),
-            GemonCommand::SelectEnv(s, Form::Long) => Some(GemonArgument::ProjectSetup(
-                GemonProjectScenario::SelectEnv(simple_arg_parser(&s, 13)),
-            )),
-            GemonCommand::Invalid => None,
-            GemonCommand::AddAuthorization(s, Form::Short) => Some(GemonArgument::ProjectSetup(
-                GemonProjectScenario::AddAuthorization(simple_arg_parser(&s, 6)),
-            )),
-            GemonCommand::AddAuthorization(s, Form::Long) => Some(GemonArgument::ProjectSetup(
-                GemonProjectScenario::AddAuthorization(simple_arg_parser(&s, 16)),
-            )),
-            GemonCommand::RemoveAuthorization => Some(GemonArgument::ProjectSetup(
-                GemonProjectScenario::RemoveAuthorization,
-            )),
-            GemonCommand::Secure => Some(GemonArgument::Secure),
-        }
+        ));
+        assert!(matches!(
+            current.last(),
+            Some(GemonArgument::ProjectSetup(GemonProjectScenario::Save(name))) if name == "login"
+        ));
+
+        assert_eq!(
+            arguments(&["env", "set", "dev", "url", "http://a::b"]),
+            arguments(&["-e=dev::url::http://a::b"])
+        );
+        assert_eq!(
+            arguments(&["-b", "print-env", "print-env"]),
+            arguments(&["env", "show", "--body=print-env"])
+        );
+        assert_eq!(
+            arguments(&["import", "postman", "shop.json", "-f"]),
+            arguments(&["import-postman=shop.json", "--file"])
+        );
+        assert_eq!(
+            arguments(&["env", "set", "dev", "mode", "print-env"]),
+            arguments(&["-e=dev::mode::print-env"])
+        );
+        assert_eq!(
+            arguments(&["env", "set", "dev", "token", "-c=abc"]),
+            arguments(&["-e=dev::token::-c=abc"])
+        );
+        assert_eq!(
+            GemonCli::translate_legacy(
+                [
+                    "gemon",
+                    "-rf=out.json",
+                    "env",
+                    "set",
+                    "dev",
+                    "-s=a",
+                    "print-env"
+                ]
+                .map(String::from)
+                .to_vec()
+            ),
+            [
+                "gemon",
+                "--response-file=out.json",
+                "env",
+                "set",
+                "dev",
+                "-s=a",
+                "print-env"
+            ]
+        );
+        assert_eq!(
+            GemonCli::translate_legacy(
+                ["gemon", "-rf=out.json", "-sec", "test=a,b"]
+                    .map(String::from)
+                    .to_vec()
+            ),
+            [
+                "gemon",
+                "test",
+                "a,b",
+                "--response-file=out.json",
+                "--secure"
+            ]
+        );
+
+        assert!(error(&["--tiemout=5"]).contains("a similar argument exists: '--timeout'"));
+        assert!(error(&["-c=login", "--prnt"]).contains("'--print'"));
+        assert!(error(&["-ct=soon"]).contains("invalid value 'soon' for '--connect-timeout <MS>'"));
+        assert!(error(&["-h=Accept"]).contains("expected key::value"));
+        assert!(error(&["-t=SOAP"]).contains("[possible values: REST, WEBSOCKET, PROTO]"));
+        assert!(error(&["-e=dev::url"]).contains("<VALUE>"));
+        assert!(error(&["--filter=users"]).contains("Unknown function 'users'"));
+        assert!(error(&["envv", "list"]).contains("a similar subcommand exists: 'env'"));
+        assert_eq!(
+            arguments(&["call", "login", "-S"]),
+            arguments(&["call", "login", "--include"])
+        );
+        assert!(error(&["call", "login", "-i"]).contains("unexpected argument '-i'"));
+    }
+
+    #[test]
+    fn project_path_is_read_before_parsing() {
+        let project_path = |args: &[&str]| {
+            let args = std::iter::once("gemon")
+                .chain(args.iter().copied())
+                .map(String::from)
+                .collect::<Vec<_>>();
+            GemonCli::project_path(&args)
+        };
+        assert_eq!(
+            project_path(&["call", "login", "--project", "../shop"]),
+            Some(PathBuf::from("../shop"))
+        );
+        assert_eq!(
+            project_path(&[
+                "--project=api/gemon.json",
+                "-c=login",
+                "--timeout={timeout}"
+            ]),
+            Some(PathBuf::from("api/gemon.json"))
+        );
+        assert_eq!(project_path(&["env", "list"]), None);
     }
 }
@@ -1,5 +1,7 @@
+use crate::completions::CompletionShell;
+use clap::ValueEnum;
 use serde_derive::{Deserialize, Serialize};
-use std::fmt;
+use std::{collections::HashMap, fmt, str::FromStr};
 
 #[derive(Debug)]
 pub enum GemonScenario {
@@ -16,6 +18,11 @@ pub enum GemonProjectScenario {
     SaveAndCall(String),
     Delete(String),
     PrintLastCall,
+    Test(Vec<String>),
+    Run(String),
+    ImportPostman(String),
+    ExportCurl(String),
+    ImportCurl(String),
     PrintEnvAll,
     PrintEnv,
     AddEnv(String, String, String),
@@ -24,67 +31,247 @@ pub enum GemonProjectScenario {
     SelectEnv(String),
     RemoveAuthorization,
     AddAuthorization(String),
-    Help,
+    AddTlsSetting(String, String),
+    RemoveTls,
+    AddProxySetting(String, String),
+    RemoveProxy,
+    PrintCookies,
+    ClearCookies,
 }
 
 #[derive(Debug, Clone)]
 pub enum MiscScenario {
+    /// The rendered help of the command it was asked for.
+    Help(String),
     Version,
+    Tui,
+    Completions(CompletionShell),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
 pub enum GemonType {
+    #[value(name = "REST")]
     Rest,
+    #[value(name = "WEBSOCKET")]
     Websocket,
+    #[value(name = "PROTO")]
     Proto,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+impl GemonType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GemonType::Rest => "REST",
+            GemonType::Websocket => "WEBSOCKET",
+            GemonType::Proto => "PROTO",
+        }
+    }
+}
+
+impl fmt::Display for GemonType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An HTTP method. Anything besides the standard methods is kept as a `Custom` verb, e.g.
+/// `PURGE` or `REPORT`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum GemonMethodType {
     Get,
     Post,
     Delete,
     Put,
     Patch,
+    Head,
+    Options,
+    Trace,
+    Custom(String),
 }
 
 impl GemonMethodType {
-    pub const ALL: [GemonMethodType; 5] = [
+    pub const ALL: [GemonMethodType; 8] = [
         GemonMethodType::Get,
         GemonMethodType::Post,
         GemonMethodType::Delete,
         GemonMethodType::Put,
         GemonMethodType::Patch,
+        GemonMethodType::Head,
+        GemonMethodType::Options,
+        GemonMethodType::Trace,
     ];
 
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             GemonMethodType::Get => "GET",
             GemonMethodType::Post => "POST",
             GemonMethodType::Delete => "DELETE",
             GemonMethodType::Put => "PUT",
             GemonMethodType::Patch => "PATCH",
+            GemonMethodType::Head => "HEAD",
+            GemonMethodType::Options => "OPTIONS",
+            GemonMethodType::Trace => "TRACE",
+            GemonMethodType::Custom(method) => method,
         }
     }
 
-    pub fn next(self) -> GemonMethodType {
+    /// Cycles through the standard methods, a custom method continues with GET.
+    pub fn next(&self) -> GemonMethodType {
+        match Self::ALL.iter().position(|method| method == self) {
+            Some(index) => Self::ALL[(index + 1) % Self::ALL.len()].clone(),
+            None => GemonMethodType::Get,
+        }
+    }
+
+    pub fn previous(&self) -> GemonMethodType {
         let index = Self::ALL
             .iter()
-            .position(|method| *method == self)
+            .position(|method| method == self)
             .unwrap_or_default();
-        Self::ALL[(index + 1) % Self::ALL.len()]
+        Self::ALL[(index + Self::ALL.len() - 1) % Self::ALL.len()].clone()
     }
+}
+
+impl FromStr for GemonMethodType {
+    type Err = String;
+
+    /// Standard methods match case-insensitively, any other valid HTTP token becomes a
+    /// `Custom` method as written.
+    fn from_str(method: &str) -> Result<Self, Self::Err> {
+        let method = method.trim();
+        if let Some(standard) = Self::ALL
+            .iter()
+            .find(|standard| standard.as_str().eq_ignore_ascii_case(method))
+        {
+            return Ok(standard.clone());
+        }
+
+        let is_token = !method.is_empty()
+            && method
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c));
+        if is_token {
+            Ok(GemonMethodType::Custom(method.to_string()))
+        } else {
+            Err(format!("'{method}' is not a valid HTTP method"))
+        }
+    }
+}
+
+impl fmt::Display for GemonMethodType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The kind of body a REST request sends. It decides how the body and form data are encoded
+/// and which Content-Type is sent, unless the request sets that header itself.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GemonBodyType {
+    #[default]
+    Json,
+    Text,
+    Xml,
+    #[serde(rename = "form")]
+    FormUrlencoded,
+    Multipart,
+    Binary,
+    None,
+}
 
-    pub fn previous(self) -> GemonMethodType {
+impl GemonBodyType {
+    pub const ALL: [GemonBodyType; 7] = [
+        GemonBodyType::Json,
+        GemonBodyType::Text,
+        GemonBodyType::Xml,
+        GemonBodyType::FormUrlencoded,
+        GemonBodyType::Multipart,
+        GemonBodyType::Binary,
+        GemonBodyType::None,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GemonBodyType::Json => "json",
+            GemonBodyType::Text => "text",
+            GemonBodyType::Xml => "xml",
+            GemonBodyType::FormUrlencoded => "form",
+            GemonBodyType::Multipart => "multipart",
+            GemonBodyType::Binary => "binary",
+            GemonBodyType::None => "none",
+        }
+    }
+
+    /// Content-Type sent with a body of this type. Multipart has none because its boundary
+    /// is only known when the form is encoded.
+    pub fn content_type(&self) -> Option<&'static str> {
+        match self {
+            GemonBodyType::Json => Some("application/json"),
+            GemonBodyType::Text => Some("text/plain; charset=utf-8"),
+            GemonBodyType::Xml => Some("application/xml"),
+            GemonBodyType::FormUrlencoded => Some("application/x-www-form-urlencoded"),
+            GemonBodyType::Binary => Some("application/octet-stream"),
+            GemonBodyType::Multipart | GemonBodyType::None => None,
+        }
+    }
+
+    /// Body type matching a Content-Type header value, if gemon knows it.
+    pub fn from_content_type(content_type: &str) -> Option<GemonBodyType> {
+        let content_type = content_type.to_ascii_lowercase();
+        let essence = content_type.split(';').next().unwrap_or_default().trim();
+        match essence {
+            "application/x-www-form-urlencoded" => Some(GemonBodyType::FormUrlencoded),
+            "multipart/form-data" => Some(GemonBodyType::Multipart),
+            "application/octet-stream" => Some(GemonBodyType::Binary),
+            _ if essence.ends_with("json") => Some(GemonBodyType::Json),
+            _ if essence.ends_with("xml") => Some(GemonBodyType::Xml),
+            _ if essence.starts_with("text/") => Some(GemonBodyType::Text),
+            _ => None,
+        }
+    }
+
+    /// Body type of a request that does not name one: form data without a body is sent
+    /// url-encoded and anything else as JSON like before body types existed. Multipart is never
+    /// inferred, so `@` values of older requests stay text.
+    pub fn infer(body: Option<&str>, form_data: &HashMap<String, String>) -> GemonBodyType {
+        match body {
+            Some(body) if !body.is_empty() => GemonBodyType::Json,
+            _ if !form_data.is_empty() => GemonBodyType::FormUrlencoded,
+            _ => GemonBodyType::Json,
+        }
+    }
+
+    pub fn next(&self) -> GemonBodyType {
         let index = Self::ALL
             .iter()
-            .position(|method| *method == self)
+            .position(|body_type| body_type == self)
             .unwrap_or_default();
-        Self::ALL[(index + Self::ALL.len() - 1) % Self::ALL.len()]
+        Self::ALL[(index + 1) % Self::ALL.len()]
     }
 }
 
-impl fmt::Display for GemonMethodType {
+impl FromStr for GemonBodyType {
+    type Err = String;
+
+    fn from_str(body_type: &str) -> Result<Self, Self::Err> {
+        let body_type = body_type.trim().to_ascii_lowercase();
+        match body_type.as_str() {
+            "form-urlencoded" | "urlencoded" => Ok(GemonBodyType::FormUrlencoded),
+            _ => Self::ALL
+                .into_iter()
+                .find(|known| known.as_str() == body_type)
+                .ok_or_else(|| {
+                    format!(
+                        "'{body_type}' is not a body type, expected one of: {}",
+                        Self::ALL.map(|known| known.as_str()).join(", ")
+                    )
+                }),
+        }
+    }
+}
+
+impl fmt::Display for GemonBodyType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str(self.as_str())
     }
@@ -95,3 +282,52 @@ pub enum GemonPrinter {
     Terminal,
     File,
 }
+
+/// How much of an exchange is printed besides the response body.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GemonOutputDetail {
+    #[default]
+    Body,
+    /// The status line, elapsed time and response headers before the body.
+    Include,
+    /// Like `Include`, preceded by the request line and the request headers.
+    Verbose,
+}
+
+/// Response statuses a call fails on, written as ranges and single codes, e.g. `500-599,404`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GemonStatusRanges(Vec<(u16, u16)>);
+
+impl GemonStatusRanges {
+    /// Every client and server error, what a plain `--fail` fails on.
+    pub fn errors() -> GemonStatusRanges {
+        GemonStatusRanges(vec![(400, 599)])
+    }
+
+    pub fn contains(&self, status: u16) -> bool {
+        self.0
+            .iter()
+            .any(|(from, to)| (*from..=*to).contains(&status))
+    }
+}
+
+impl FromStr for GemonStatusRanges {
+    type Err = String;
+
+    fn from_str(ranges: &str) -> Result<Self, Self::Err> {
+        let status = |status: &str| {
+            status
+                .trim()
+                .parse::<u16>()
+                .map_err(|_| format!("'{}' is not a status code", status.trim()))
+        };
+        ranges
+            .split(',')
+            .map(|range| match range.split_once('-') {
+                Some((from, to)) => Ok((status(from)?, status(to)?)),
+                None => status(range).map(|status| (status, status)),
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(GemonStatusRanges)
+    }
+}
@@ -1,5 +1,4 @@
-pub static DEFAULT_CONTENT_TYPE: &str = "application/json";
-pub static DEFAULT_ACCEPT: &str = "application/json";
 pub static PROJECT_ROOT_FILE: &str = "gemon.json";
+pub static PROJECT_ENV: &str = "GEMON_PROJECT";
 pub static NO_ENV: &str = "no_env";
 pub static AUTHORIZATION: &str = "authorization";
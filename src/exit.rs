@@ -0,0 +1,84 @@
+use std::{error::Error, fmt};
+
+/// A failure that ends gemon with its own exit code, so scripts can tell them apart. Any other
+/// error exits with 1.
+#[derive(Debug)]
+pub enum ExitError {
+    /// The request could not be sent or its response could not be read.
+    Transport(String),
+    /// The response status is one the call was asked to fail on with `--fail`.
+    HttpStatus(u16),
+    /// Saved request tests or run steps did not pass.
+    Assertion(String),
+}
+
+impl ExitError {
+    pub fn code(&self) -> u8 {
+        match self {
+            ExitError::Transport(_) => 2,
+            ExitError::HttpStatus(_) => 3,
+            ExitError::Assertion(_) => 4,
+        }
+    }
+
+    /// Wraps the connection, TLS and timeout errors of any request type as transport errors,
+    /// other errors such as an invalid url or a missing body file are kept as they are.
+    pub fn from_request_error(err: Box<dyn Error>) -> Box<dyn Error> {
+        let transport = match err.downcast_ref::<reqwest::Error>() {
+            Some(err) => !err.is_builder(),
+            None => {
+                err.is::<tokio_tungstenite::tungstenite::Error>()
+                    || err.is::<tonic::transport::Error>()
+            }
+        };
+        match transport {
+            true => Box::new(ExitError::Transport(err.to_string())),
+            false => err,
+        }
+    }
+}
+
+impl fmt::Display for ExitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExitError::Transport(message) => write!(f, "request failed: {message}"),
+            ExitError::HttpStatus(status) => write!(f, "response status {status}"),
+            ExitError::Assertion(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl Error for ExitError {}
+
+/// Exit code of an error returned by `gemon::run`.
+pub fn exit_code(err: &(dyn Error + 'static)) -> u8 {
+    err.downcast_ref::<ExitError>()
+        .map(ExitError::code)
+        .unwrap_or(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{exit_code, ExitError};
+    use crate::{config::types::GemonStatusRanges, project::ProjectError};
+    use std::error::Error;
+
+    #[test]
+    fn failures_have_distinct_exit_codes() {
+        let transport: Box<dyn Error> = Box::new(ExitError::Transport(String::from("refused")));
+        let status: Box<dyn Error> = Box::new(ExitError::HttpStatus(500));
+        let assertion: Box<dyn Error> = Box::new(ExitError::Assertion(String::from("1 failed")));
+        let other: Box<dyn Error> = ProjectError::from("Request not found");
+
+        assert_eq!(exit_code(transport.as_ref()), 2);
+        assert_eq!(exit_code(status.as_ref()), 3);
+        assert_eq!(exit_code(assertion.as_ref()), 4);
+        assert_eq!(exit_code(other.as_ref()), 1);
+
+        let ranges: GemonStatusRanges = "500-599, 404".parse().unwrap();
+        assert!(ranges.contains(404) && ranges.contains(503));
+        assert!(!ranges.contains(400));
+        assert!(GemonStatusRanges::errors().contains(400));
+        assert!("5xx".parse::<GemonStatusRanges>().is_err());
+    }
+}
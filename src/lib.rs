@@ -1,13 +1,21 @@
-use crate::config::{arguments::GemonArguments, types::GemonScenario, GemonConfig};
+use crate::config::{
+    arguments::GemonArguments,
+    types::{GemonScenario, MiscScenario},
+    GemonConfig,
+};
 use config::effector::Effector;
 use misc::Misc;
 use project::Project;
 use request::Request;
 use std::error::Error;
 
+pub use exit::exit_code;
+
 mod command;
+mod completions;
 mod config;
 mod constants;
+mod exit;
 mod misc;
 mod printer;
 mod project;
@@ -17,14 +25,27 @@ mod tui;
 type EmptyResult = Result<(), Box<dyn Error>>;
 
 pub async fn run(args: Vec<String>) -> Result<(), Box<dyn Error>> {
+    // --project picks the project before anything reads it, the completions included
+    if let Some(path) = command::GemonCli::project_path(completions::command_line(&args)) {
+        project::project_handler::set_project_override(path);
+    }
+
+    // Completion scripts call gemon to list the candidates, this answers them and exits
+    completions::complete_from_env();
+
     if args
         .get(1)
-        .map(|arg| matches!(arg.as_str(), "tui" | "--tui" | "-i" | "--interactive"))
+        .map(|arg| matches!(arg.as_str(), "--tui" | "-i" | "--interactive"))
         .unwrap_or(false)
     {
         return tui::run().await;
     }
 
+    // Colors are only written to a terminal and when NO_COLOR is not set
+    if !printer::highlight::colors_enabled() {
+        colored::control::set_override(false);
+    }
+
     // Apply env
     let args = Effector::apply_env_to_args(args);
     // Parse arguments
@@ -37,6 +58,7 @@ pub async fn run(args: Vec<String>) -> Result<(), Box<dyn Error>> {
         GemonScenario::Project(project_scenario) => {
             Project::execute(&gemon_config, project_scenario).await
         }
+        GemonScenario::Misc(MiscScenario::Tui) => tui::run().await,
         GemonScenario::Misc(misc_scenario) => Misc::execute(misc_scenario),
     }
 }
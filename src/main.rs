@@ -1,7 +1,12 @@
-use std::{env, error::Error};
+use std::{env, process::ExitCode};
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn Error>> {
-    gemon::run(env::args().collect()).await?;
-    Ok(())
+async fn main() -> ExitCode {
+    match gemon::run(env::args().collect()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {err}");
+            ExitCode::from(gemon::exit_code(err.as_ref()))
+        }
+    }
 }
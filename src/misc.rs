@@ -1,4 +1,5 @@
-use crate::{config::types::MiscScenario, EmptyResult};
+use crate::{completions, config::types::MiscScenario, EmptyResult};
+use std::io;
 
 pub struct Misc;
 
@@ -9,10 +10,19 @@ impl Misc {
 
     pub fn execute(scenario: &MiscScenario) -> EmptyResult {
         match scenario {
+            MiscScenario::Help(help) => {
+                println!("{help}");
+                Ok(())
+            }
             MiscScenario::Version => {
                 Self::version();
                 Ok(())
             }
+            MiscScenario::Tui => Ok(()),
+            MiscScenario::Completions(shell) => {
+                completions::write_script(*shell, &mut io::stdout())?;
+                Ok(())
+            }
         }
     }
 }
@@ -1,13 +1,18 @@
-use crate::config::{types::GemonPrinter, GemonConfig};
-use bytes::Bytes;
+use crate::{
+    config::{types::GemonPrinter, GemonConfig},
+    request::request_builder::GemonResponse,
+};
 
 use self::{file_printer::FilePrinter, terminal_printer::TerminalPrinter};
 
 pub mod file_printer;
+pub mod filter;
+pub mod format;
+pub mod highlight;
 pub mod terminal_printer;
 
 pub trait Printer {
-    fn print(&self, bytes: &Bytes) -> Result<(), std::io::Error>;
+    fn print(&self, response: &GemonResponse) -> Result<(), std::io::Error>;
 }
 
 pub struct PrinterBuilder {}
@@ -15,10 +20,17 @@ pub struct PrinterBuilder {}
 impl PrinterBuilder {
     pub fn build(config: &GemonConfig) -> Box<dyn Printer> {
         match config.gemon_printer() {
-            GemonPrinter::Terminal => Box::new(TerminalPrinter::new()),
+            GemonPrinter::Terminal => {
+                let mut terminal_printer = TerminalPrinter::new();
+                terminal_printer.set_detail(config.gemon_output_detail());
+                terminal_printer.set_filter(config.gemon_filter().cloned());
+                Box::new(terminal_printer)
+            }
             GemonPrinter::File => {
                 let mut file_printer = FilePrinter::new(config.gemon_also_print_to_terminal());
                 file_printer.set_file_path(config.gemon_response_file_path());
+                file_printer.set_detail(config.gemon_output_detail());
+                file_printer.set_filter(config.gemon_filter().cloned());
                 Box::new(file_printer)
             }
         }
@@ -1,10 +1,17 @@
-use super::Printer;
-use serde_json::Value;
+use super::{
+    filter::Filter,
+    format::{content_type, format_body, head, FormattedBody},
+    highlight::{colorize, Syntax},
+    Printer,
+};
+use crate::{config::types::GemonOutputDetail, request::request_builder::GemonResponse};
 use std::fs;
 
 pub struct FilePrinter {
     file_path: Option<String>,
     also_print_to_terminal: bool,
+    detail: GemonOutputDetail,
+    filter: Option<Filter>,
 }
 
 impl FilePrinter {
@@ -12,22 +19,68 @@ impl FilePrinter {
         FilePrinter {
             file_path: None,
             also_print_to_terminal,
+            detail: GemonOutputDetail::Body,
+            filter: None,
         }
     }
 
     pub fn set_file_path(&mut self, file_path: Option<String>) {
         self.file_path = file_path;
     }
+
+    pub fn set_detail(&mut self, detail: GemonOutputDetail) {
+        self.detail = detail;
+    }
+
+    pub fn set_filter(&mut self, filter: Option<Filter>) {
+        self.filter = filter;
+    }
 }
 
 impl Printer for FilePrinter {
-    fn print(&self, bytes: &bytes::Bytes) -> Result<(), std::io::Error> {
-        let response_value: Value = serde_json::from_slice(bytes)?;
-        let pretty_response = serde_json::to_string_pretty(&response_value)?;
+    /// Writes JSON and XML pretty printed, other text as is and binary bodies raw. The status
+    /// line and headers, when asked for, are written before the body followed by an empty line.
+    /// With a filter only the values it picks out of a JSON body are written.
+    fn print(&self, response: &GemonResponse) -> Result<(), std::io::Error> {
+        let path = self.file_path.as_ref().expect("File path missing!");
+        let bytes = response.data();
+        let content_type = content_type(response.headers());
+        let formatted = match &self.filter {
+            Some(filter) => filter.format(bytes).map_err(std::io::Error::other)?,
+            None => format_body(bytes, content_type),
+        };
+        let (content, shown) = match formatted {
+            // HEAD and OPTIONS responses usually have no body
+            FormattedBody::Empty => (Vec::new(), None),
+            FormattedBody::Text(text) => {
+                let syntax = match self.filter {
+                    Some(_) => Syntax::Json,
+                    None => Syntax::detect(content_type, &text),
+                };
+                (text.clone().into_bytes(), Some(colorize(&text, syntax)))
+            }
+            binary => (
+                bytes.to_vec(),
+                Some(format!("{}, written to {path}", binary.summary())),
+            ),
+        };
+        let head = head(response, self.detail);
+
         if self.also_print_to_terminal {
-            println!("{}", pretty_response);
+            let shown: Vec<&str> = [head.as_deref(), shown.as_deref()]
+                .into_iter()
+                .flatten()
+                .collect();
+            if !shown.is_empty() {
+                println!("{}", shown.join("\n\n"));
+            }
         }
-        let path = self.file_path.as_ref().expect("File path missing!");
-        fs::write(path, pretty_response)
+
+        let mut file = head.map(String::into_bytes).unwrap_or_default();
+        if !file.is_empty() && !content.is_empty() {
+            file.extend_from_slice(b"\n\n");
+        }
+        file.extend_from_slice(&content);
+        fs::write(path, file)
     }
 }
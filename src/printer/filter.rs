@@ -0,0 +1,327 @@
+use super::format::FormattedBody;
+use serde_json::Value;
+use std::str::FromStr;
+
+/// One step of a filter, applied to every value the previous step produced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    Field(String),
+    /// Negative indexes count from the end of the array.
+    Index(i64),
+    /// Every element of an array or every value of an object.
+    Iterate,
+    Length,
+    Keys,
+}
+
+/// A jq-like expression picking values out of a JSON response, e.g. `.users[0].name`,
+/// `.users[].id`, `$.data.items[*]` or `.users | length`. Fields are read with `.name`,
+/// `."odd name"` or `["odd name"]`, `[]`, `[*]` and `.*` iterate, and `|` passes the values on
+/// to `length` or `keys` or another path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    expression: String,
+    steps: Vec<Step>,
+}
+
+impl Filter {
+    /// The values the filter picks out of `value`, a missing field or index gives `null`.
+    pub fn apply(&self, value: &Value) -> Result<Vec<Value>, String> {
+        self.steps
+            .iter()
+            .try_fold(vec![value.clone()], |values, step| {
+                values.iter().try_fold(Vec::new(), |mut results, value| {
+                    results.extend(apply_step(step, value)?);
+                    Ok(results)
+                })
+            })
+    }
+
+    /// Filters a JSON response body, every picked value is pretty printed on its own.
+    pub fn format(&self, bytes: &[u8]) -> Result<FormattedBody, String> {
+        let value = serde_json::from_slice::<Value>(bytes).map_err(|_| {
+            format!(
+                "Cannot apply filter '{}', the response is not JSON",
+                self.expression
+            )
+        })?;
+        let results = self.apply(&value)?;
+        if results.is_empty() {
+            return Ok(FormattedBody::Empty);
+        }
+        Ok(FormattedBody::Text(
+            results
+                .iter()
+                .map(|value| serde_json::to_string_pretty(value).unwrap_or_default())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ))
+    }
+}
+
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn apply_step(step: &Step, value: &Value) -> Result<Vec<Value>, String> {
+    let result = match (step, value) {
+        (Step::Field(_) | Step::Index(_), Value::Null) => Value::Null,
+        (Step::Field(name), Value::Object(object)) => {
+            object.get(name).cloned().unwrap_or(Value::Null)
+        }
+        (Step::Index(index), Value::Array(array)) => {
+            let index = match *index < 0 {
+                true => array.len() as i64 + index,
+                false => *index,
+            };
+            usize::try_from(index)
+                .ok()
+                .and_then(|index| array.get(index))
+                .cloned()
+                .unwrap_or(Value::Null)
+        }
+        (Step::Iterate, Value::Array(array)) => return Ok(array.clone()),
+        (Step::Iterate, Value::Object(object)) => return Ok(object.values().cloned().collect()),
+        (Step::Length, Value::Null) => Value::from(0),
+        (Step::Length, Value::String(text)) => Value::from(text.chars().count()),
+        (Step::Length, Value::Array(array)) => Value::from(array.len()),
+        (Step::Length, Value::Object(object)) => Value::from(object.len()),
+        (Step::Keys, Value::Object(object)) => {
+            let mut keys = object.keys().cloned().collect::<Vec<_>>();
+            keys.sort();
+            Value::from(keys)
+        }
+        (Step::Keys, Value::Array(array)) => Value::from((0..array.len()).collect::<Vec<_>>()),
+        (Step::Field(name), _) => {
+            return Err(format!("Cannot read field '{name}' of {}", kind(value)))
+        }
+        (Step::Index(index), _) => {
+            return Err(format!("Cannot read index {index} of {}", kind(value)))
+        }
+        (Step::Iterate, _) => return Err(format!("Cannot iterate over {}", kind(value))),
+        (Step::Length, _) => return Err(format!("{} has no length", kind(value))),
+        (Step::Keys, _) => return Err(format!("{} has no keys", kind(value))),
+    };
+    Ok(vec![result])
+}
+
+/// Reads a quoted name starting at the opening quote, returns it with the position after the
+/// closing quote. A backslash escapes the next character.
+fn quoted(chars: &[char], start: usize) -> Result<(String, usize), String> {
+    let quote = chars[start];
+    let mut name = String::new();
+    let mut position = start + 1;
+    while let Some(&character) = chars.get(position) {
+        match character {
+            '\\' => {
+                name.extend(chars.get(position + 1));
+                position += 2;
+            }
+            _ if character == quote => return Ok((name, position + 1)),
+            _ => {
+                name.push(character);
+                position += 1;
+            }
+        }
+    }
+    Err(String::from("Unterminated quote in filter"))
+}
+
+fn is_name_char(character: char) -> bool {
+    character.is_alphanumeric() || matches!(character, '_' | '-')
+}
+
+/// Reads a bracket step starting at `[`: an index, a quoted field or an iteration.
+fn bracket(chars: &[char], start: usize) -> Result<(Step, usize), String> {
+    let mut position = start + 1;
+    while chars
+        .get(position)
+        .is_some_and(|character| character.is_whitespace())
+    {
+        position += 1;
+    }
+    let (step, end) = match chars.get(position) {
+        Some('"' | '\'') => {
+            let (name, end) = quoted(chars, position)?;
+            (Step::Field(name), end)
+        }
+        _ => {
+            let end = chars[position..]
+                .iter()
+                .position(|character| *character == ']')
+                .map(|offset| position + offset)
+                .ok_or_else(|| String::from("Missing ']' in filter"))?;
+            let inner = chars[position..end].iter().collect::<String>();
+            let step = match inner.trim() {
+                "" | "*" => Step::Iterate,
+                index => Step::Index(
+                    index
+                        .parse()
+                        .map_err(|_| format!("'{index}' is not an array index"))?,
+                ),
+            };
+            (step, end)
+        }
+    };
+    let mut position = end;
+    while chars
+        .get(position)
+        .is_some_and(|character| character.is_whitespace())
+    {
+        position += 1;
+    }
+    match chars.get(position) {
+        Some(']') => Ok((step, position + 1)),
+        _ => Err(String::from("Missing ']' in filter")),
+    }
+}
+
+impl FromStr for Filter {
+    type Err = String;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        let chars = expression.chars().collect::<Vec<_>>();
+        let mut steps = Vec::new();
+        let mut position = 0;
+        // Whether the next term of a pipe is expected, only there names are functions
+        let mut term_start = true;
+
+        while let Some(&character) = chars.get(position) {
+            match character {
+                _ if character.is_whitespace() => position += 1,
+                '|' if !term_start => {
+                    term_start = true;
+                    position += 1;
+                }
+                '$' if term_start => {
+                    term_start = false;
+                    position += 1;
+                }
+                '.' => {
+                    term_start = false;
+                    position += 1;
+                    match chars.get(position) {
+                        Some('*') => {
+                            steps.push(Step::Iterate);
+                            position += 1;
+                        }
+                        Some('"' | '\'') => {
+                            let (name, end) = quoted(&chars, position)?;
+                            steps.push(Step::Field(name));
+                            position = end;
+                        }
+                        Some(&next) if is_name_char(next) => {
+                            let end = chars[position..]
+                                .iter()
+                                .position(|character| !is_name_char(*character))
+                                .map_or(chars.len(), |offset| position + offset);
+                            steps.push(Step::Field(chars[position..end].iter().collect()));
+                            position = end;
+                        }
+                        Some('.') => {
+                            return Err(String::from("Recursive descent '..' is not supported"))
+                        }
+                        _ => {}
+                    }
+                }
+                '[' if !term_start => {
+                    let (step, end) = bracket(&chars, position)?;
+                    steps.push(step);
+                    position = end;
+                }
+                _ if term_start && is_name_char(character) => {
+                    let end = chars[position..]
+                        .iter()
+                        .position(|character| !is_name_char(*character))
+                        .map_or(chars.len(), |offset| position + offset);
+                    let name = chars[position..end].iter().collect::<String>();
+                    steps.push(match name.as_str() {
+                        "length" => Step::Length,
+                        "keys" => Step::Keys,
+                        _ => {
+                            return Err(format!("Unknown function '{name}', paths start with '.'"))
+                        }
+                    });
+                    term_start = false;
+                    position = end;
+                }
+                _ => {
+                    return Err(format!(
+                        "Unexpected '{character}' at position {} of the filter",
+                        position + 1
+                    ))
+                }
+            }
+        }
+
+        if term_start {
+            return Err(String::from("The filter is empty or ends with '|'"));
+        }
+        Ok(Filter {
+            expression: expression.trim().to_string(),
+            steps,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Filter;
+    use serde_json::{json, Value};
+
+    fn filter(expression: &str, value: &Value) -> Result<Vec<Value>, String> {
+        expression.parse::<Filter>()?.apply(value)
+    }
+
+    #[test]
+    fn filters_pick_fields_indexes_and_iterations() {
+        let value = json!({
+            "users": [
+                {"id": 1, "name": "Ann", "tags": ["a", "b"]},
+                {"id": 2, "name": "Bob", "tags": []}
+            ],
+            "page info": {"next": null}
+        });
+
+        assert_eq!(filter(".", &value).unwrap(), vec![value.clone()]);
+        assert_eq!(
+            filter(".users[0].name", &value).unwrap(),
+            vec![json!("Ann")]
+        );
+        assert_eq!(filter("$.users[-1].id", &value).unwrap(), vec![json!(2)]);
+        assert_eq!(
+            filter(".users[].id", &value).unwrap(),
+            vec![json!(1), json!(2)]
+        );
+        assert_eq!(
+            filter(".users[*].missing", &value).unwrap(),
+            vec![Value::Null, Value::Null]
+        );
+        assert_eq!(
+            filter(r#".["page info"].next"#, &value).unwrap(),
+            vec![Value::Null]
+        );
+        assert_eq!(
+            filter(r#"."page info" | keys"#, &value).unwrap(),
+            vec![json!(["next"])]
+        );
+        assert_eq!(
+            filter(".users[] | .tags | length", &value).unwrap(),
+            vec![json!(2), json!(0)]
+        );
+        assert_eq!(filter(".users[5]", &value).unwrap(), vec![Value::Null]);
+
+        assert!(filter(".users.name", &value).is_err());
+        assert!(filter(".users[0].id[]", &value).is_err());
+        assert!("users".parse::<Filter>().is_err());
+        assert!(".users |".parse::<Filter>().is_err());
+        assert!(".users[0".parse::<Filter>().is_err());
+    }
+}
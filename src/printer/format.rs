@@ -0,0 +1,303 @@
+use crate::{
+    config::types::{GemonBodyType, GemonOutputDetail},
+    request::request_builder::GemonResponse,
+};
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Content types that are never shown as text, even when their bytes happen to be UTF-8.
+const BINARY_CONTENT_TYPES: [&str; 10] = [
+    "image/",
+    "audio/",
+    "video/",
+    "font/",
+    "application/octet-stream",
+    "application/pdf",
+    "application/zip",
+    "application/gzip",
+    "application/grpc",
+    "application/x-protobuf",
+];
+
+/// A response body as gemon shows it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormattedBody {
+    Empty,
+    /// Pretty printed JSON or XML, or any other text as it was received.
+    Text(String),
+    Binary {
+        size: usize,
+        content_type: Option<String>,
+    },
+}
+
+impl FormattedBody {
+    /// One line describing a binary body, e.g. `Binary response: 5120 bytes (image/png)`.
+    pub fn summary(&self) -> String {
+        match self {
+            FormattedBody::Empty => String::from("Empty response"),
+            FormattedBody::Text(text) => format!("Text response: {} bytes", text.len()),
+            FormattedBody::Binary {
+                size,
+                content_type: Some(content_type),
+            } => format!("Binary response: {size} bytes ({content_type})"),
+            FormattedBody::Binary {
+                size,
+                content_type: None,
+            } => format!("Binary response: {size} bytes"),
+        }
+    }
+}
+
+/// The Content-Type of a response, header names are matched case-insensitively.
+pub fn content_type(headers: &HashMap<String, String>) -> Option<&str> {
+    headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("content-type"))
+        .map(|(_, value)| value.as_str())
+}
+
+/// Formats a body by its Content-Type. JSON and XML are pretty printed, other text is kept as
+/// is and anything else is binary. Without a Content-Type JSON is tried first.
+pub fn format_body(bytes: &[u8], content_type: Option<&str>) -> FormattedBody {
+    if bytes.is_empty() {
+        return FormattedBody::Empty;
+    }
+    let binary = || FormattedBody::Binary {
+        size: bytes.len(),
+        content_type: content_type.map(String::from),
+    };
+    let essence = content_type
+        .map(|content_type| content_type.to_ascii_lowercase())
+        .unwrap_or_default();
+    let body_type = GemonBodyType::from_content_type(&essence);
+    if body_type != Some(GemonBodyType::Xml)
+        && BINARY_CONTENT_TYPES
+            .iter()
+            .any(|binary_type| essence.starts_with(binary_type))
+    {
+        return binary();
+    }
+
+    let Ok(text) = std::str::from_utf8(bytes) else {
+        return binary();
+    };
+    if text.contains('\0') {
+        return binary();
+    }
+    match body_type {
+        Some(GemonBodyType::Json) | None => match serde_json::from_str::<Value>(text) {
+            Ok(value) => FormattedBody::Text(
+                serde_json::to_string_pretty(&value).unwrap_or_else(|_| text.to_string()),
+            ),
+            Err(_) => FormattedBody::Text(text.to_string()),
+        },
+        Some(GemonBodyType::Xml) => {
+            FormattedBody::Text(pretty_xml(text).unwrap_or_else(|| text.to_string()))
+        }
+        _ => FormattedBody::Text(text.to_string()),
+    }
+}
+
+/// The status line of a response with the time it took, e.g. `HTTP/1.1 200 OK (42 ms)`.
+pub fn status_line(response: &GemonResponse) -> String {
+    let mut line = format!(
+        "{} {}",
+        response.version().unwrap_or("HTTP"),
+        response.status()
+    );
+    if let Some(reason) = StatusCode::from_u16(response.status())
+        .ok()
+        .and_then(|status| status.canonical_reason())
+    {
+        line.push_str(&format!(" {reason}"));
+    }
+    if let Some(elapsed) = response.elapsed() {
+        line.push_str(&format!(" ({} ms)", elapsed.as_millis()));
+    }
+    line
+}
+
+fn header_lines(prefix: &str, mut headers: Vec<(&String, &String)>) -> Vec<String> {
+    headers.sort();
+    headers
+        .into_iter()
+        .map(|(key, value)| format!("{prefix}{key}: {value}"))
+        .collect()
+}
+
+/// Lines printed before the body, `None` when only the body is shown. Headers are sorted by
+/// name, verbose output starts with the request and prefixes its lines with `>` and the
+/// response lines with `<`.
+pub fn head(response: &GemonResponse, detail: GemonOutputDetail) -> Option<String> {
+    let prefix = match detail {
+        GemonOutputDetail::Body => return None,
+        GemonOutputDetail::Include => "",
+        GemonOutputDetail::Verbose => "< ",
+    };
+    let mut lines = Vec::new();
+    if let (GemonOutputDetail::Verbose, Some(request)) = (detail, response.request()) {
+        lines.push(format!("> {} {}", request.method, request.url));
+        let headers = request.headers.iter().map(|(key, value)| (key, value));
+        lines.extend(header_lines("> ", headers.collect()));
+        lines.push(String::from(">"));
+    }
+    lines.push(format!("{prefix}{}", status_line(response)));
+    lines.extend(header_lines(prefix, response.headers().iter().collect()));
+    Some(lines.join("\n"))
+}
+
+/// Length of the tag at the start of `xml`, quoted attribute values may contain `>`.
+fn tag_len(xml: &str) -> Option<usize> {
+    for (open, close) in [("<!--", "-->"), ("<![CDATA[", "]]>")] {
+        if xml.starts_with(open) {
+            return xml.find(close).map(|end| end + close.len());
+        }
+    }
+    let mut quote = None;
+    for (index, character) in xml.char_indices() {
+        match (quote, character) {
+            (None, '"' | '\'') => quote = Some(character),
+            (Some(open), _) if open == character => quote = None,
+            (None, '>') => return Some(index + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Indents every element on its own line, elements holding only text stay on one line.
+/// Returns `None` for malformed XML.
+pub fn pretty_xml(xml: &str) -> Option<String> {
+    let mut lines = Vec::new();
+    let mut depth = 0usize;
+    let mut rest = xml.trim();
+    let indent = |depth: usize| "  ".repeat(depth);
+
+    while !rest.is_empty() {
+        if !rest.starts_with('<') {
+            let end = rest.find('<').unwrap_or(rest.len());
+            lines.push(format!("{}{}", indent(depth), rest[..end].trim()));
+            rest = rest[end..].trim_start();
+            continue;
+        }
+
+        let tag = &rest[..tag_len(rest)?];
+        rest = rest[tag.len()..].trim_start();
+        if tag.starts_with("</") {
+            depth = depth.checked_sub(1)?;
+            lines.push(format!("{}{tag}", indent(depth)));
+        } else if tag.starts_with("<?") || tag.starts_with("<!") || tag.ends_with("/>") {
+            lines.push(format!("{}{tag}", indent(depth)));
+        } else {
+            let text_len = rest.find('<').unwrap_or(rest.len());
+            let after_text = &rest[text_len..];
+            if after_text.starts_with("</") {
+                let close = &after_text[..tag_len(after_text)?];
+                let text = rest[..text_len].trim();
+                lines.push(format!("{}{tag}{text}{close}", indent(depth)));
+                rest = after_text[close.len()..].trim_start();
+            } else {
+                lines.push(format!("{}{tag}", indent(depth)));
+                depth += 1;
+            }
+        }
+    }
+    Some(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_body, head, pretty_xml, FormattedBody};
+    use crate::{
+        config::types::GemonOutputDetail,
+        request::request_builder::{GemonResponse, SentRequest},
+    };
+    use bytes::Bytes;
+    use std::{collections::HashMap, time::Duration};
+
+    #[test]
+    fn bodies_are_formatted_by_content_type() {
+        assert_eq!(
+            format_body(br#"{"a":1}"#, None),
+            FormattedBody::Text(String::from("{\n  \"a\": 1\n}"))
+        );
+        assert_eq!(
+            format_body(b"<h1>Bad Gateway</h1>", Some("text/html; charset=utf-8")),
+            FormattedBody::Text(String::from("<h1>Bad Gateway</h1>"))
+        );
+        assert_eq!(
+            format_body(b"not json", Some("application/json")),
+            FormattedBody::Text(String::from("not json"))
+        );
+        assert_eq!(
+            format_body(&[0xff, 0xd8, 0xff], None),
+            FormattedBody::Binary {
+                size: 3,
+                content_type: None
+            }
+        );
+        assert!(matches!(
+            format_body(b"GIF89a", Some("image/gif")),
+            FormattedBody::Binary { size: 6, .. }
+        ));
+        assert_eq!(format_body(b"", Some("text/plain")), FormattedBody::Empty);
+    }
+
+    #[test]
+    fn xml_is_indented() {
+        let xml = r#"<?xml version="1.0"?><users><user id="1" note="a>b"><name>Ann</name><tags/></user><!-- end --></users>"#;
+
+        assert_eq!(
+            pretty_xml(xml).unwrap(),
+            [
+                r#"<?xml version="1.0"?>"#,
+                "<users>",
+                r#"  <user id="1" note="a>b">"#,
+                "    <name>Ann</name>",
+                "    <tags/>",
+                "  </user>",
+                "  <!-- end -->",
+                "</users>",
+            ]
+            .join("\n")
+        );
+        assert_eq!(pretty_xml("<a>"), Some(String::from("<a>")));
+        assert_eq!(pretty_xml("</a>"), None);
+    }
+
+    #[test]
+    fn head_shows_the_status_line_and_sorted_headers() {
+        let headers = HashMap::from([
+            (String::from("x-request-id"), String::from("7")),
+            (String::from("content-type"), String::from("text/plain")),
+        ]);
+        let mut response = GemonResponse::new(Bytes::from("created"), 201, headers)
+            .with_version(String::from("HTTP/1.1"))
+            .with_request(SentRequest {
+                method: String::from("POST"),
+                url: String::from("http://localhost/users"),
+                headers: vec![(String::from("content-type"), String::from("text/plain"))],
+            });
+        response.set_elapsed(Duration::from_millis(42));
+
+        assert_eq!(head(&response, GemonOutputDetail::Body), None);
+        assert_eq!(
+            head(&response, GemonOutputDetail::Include).unwrap(),
+            "HTTP/1.1 201 Created (42 ms)\ncontent-type: text/plain\nx-request-id: 7"
+        );
+        assert_eq!(
+            head(&response, GemonOutputDetail::Verbose).unwrap(),
+            [
+                "> POST http://localhost/users",
+                "> content-type: text/plain",
+                ">",
+                "< HTTP/1.1 201 Created (42 ms)",
+                "< content-type: text/plain",
+                "< x-request-id: 7",
+            ]
+            .join("\n")
+        );
+    }
+}
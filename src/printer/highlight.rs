@@ -0,0 +1,285 @@
+use colored::Colorize;
+use std::{
+    env,
+    io::{self, IsTerminal},
+};
+
+/// How a response body is highlighted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Syntax {
+    Json,
+    /// XML and HTML.
+    Markup,
+    #[default]
+    Plain,
+}
+
+impl Syntax {
+    /// Syntax of a formatted body by its Content-Type, bodies without a known one are
+    /// recognized by their first character.
+    pub fn detect(content_type: Option<&str>, text: &str) -> Syntax {
+        let content_type = content_type.unwrap_or_default().to_ascii_lowercase();
+        let essence = content_type.split(';').next().unwrap_or_default().trim();
+        match essence {
+            _ if essence.ends_with("json") => Syntax::Json,
+            _ if essence.ends_with("xml") || essence.ends_with("html") => Syntax::Markup,
+            _ => match text.trim_start().chars().next() {
+                Some('{' | '[') => Syntax::Json,
+                Some('<') => Syntax::Markup,
+                _ => Syntax::Plain,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Plain,
+    Key,
+    String,
+    Number,
+    Boolean,
+    Null,
+    Tag,
+    Attribute,
+    Comment,
+}
+
+/// Whether the terminal output is colored: stdout has to be a terminal and `NO_COLOR` unset.
+pub fn colors_enabled() -> bool {
+    !no_color_requested() && io::stdout().is_terminal()
+}
+
+/// Whether the user turned colors off with a non-empty `NO_COLOR`, see https://no-color.org.
+pub fn no_color_requested() -> bool {
+    env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+}
+
+struct Tokens<'a> {
+    text: &'a str,
+    ranges: Vec<(TokenKind, usize, usize)>,
+}
+
+impl Tokens<'_> {
+    /// Adds the text from `start` to `end`, joining neighbouring plain text.
+    fn push(&mut self, kind: TokenKind, start: usize, end: usize) {
+        if start == end {
+            return;
+        }
+        match self.ranges.last_mut() {
+            Some((TokenKind::Plain, _, last_end)) if kind == TokenKind::Plain => *last_end = end,
+            _ => self.ranges.push((kind, start, end)),
+        }
+    }
+}
+
+fn next_char(text: &str, index: usize) -> usize {
+    index + text[index..].chars().next().map_or(1, char::len_utf8)
+}
+
+/// Index after the quote closing the string that starts at `start`, backslashes escape.
+fn quoted_end(text: &str, start: usize) -> usize {
+    let quote = text.as_bytes()[start];
+    let mut index = start + 1;
+    while let Some(&byte) = text.as_bytes().get(index) {
+        match byte {
+            b'\\' => index += 2,
+            _ if byte == quote => return index + 1,
+            _ => index += 1,
+        }
+    }
+    text.len()
+}
+
+fn json_tokens(tokens: &mut Tokens) {
+    let text = tokens.text;
+    let bytes = text.as_bytes();
+    let mut index = 0;
+    while index < bytes.len() {
+        let rest = &text[index..];
+        let (kind, end) = match bytes[index] {
+            b'"' => {
+                let end = quoted_end(text, index).min(text.len());
+                match text[end..].trim_start().starts_with(':') {
+                    true => (TokenKind::Key, end),
+                    false => (TokenKind::String, end),
+                }
+            }
+            b'-' | b'0'..=b'9' => {
+                let length = rest[1..]
+                    .find(|character: char| {
+                        !matches!(character, '0'..='9' | '.' | 'e' | 'E' | '+' | '-')
+                    })
+                    .map_or(rest.len(), |length| length + 1);
+                (TokenKind::Number, index + length)
+            }
+            _ if rest.starts_with("true") => (TokenKind::Boolean, index + 4),
+            _ if rest.starts_with("false") => (TokenKind::Boolean, index + 5),
+            _ if rest.starts_with("null") => (TokenKind::Null, index + 4),
+            _ => (TokenKind::Plain, next_char(text, index)),
+        };
+        tokens.push(kind, index, end);
+        index = end;
+    }
+}
+
+fn markup_tokens(tokens: &mut Tokens) {
+    let text = tokens.text;
+    let bytes = text.as_bytes();
+    let mut index = 0;
+    while index < bytes.len() {
+        let rest = &text[index..];
+        if let Some((open, close, kind)) = [
+            ("<!--", "-->", TokenKind::Comment),
+            ("<![CDATA[", "]]>", TokenKind::Plain),
+        ]
+        .into_iter()
+        .find(|(open, _, _)| rest.starts_with(open))
+        {
+            let end = rest[open.len()..]
+                .find(close)
+                .map_or(text.len(), |end| index + open.len() + end + close.len());
+            tokens.push(kind, index, end);
+            index = end;
+            continue;
+        }
+        if bytes[index] != b'<' {
+            let end = rest.find('<').map_or(text.len(), |end| index + end);
+            tokens.push(TokenKind::Plain, index, end);
+            index = end;
+            continue;
+        }
+
+        // The opening bracket and name of a tag, then its attributes until the closing bracket
+        let name_start = match bytes.get(index + 1) {
+            Some(b'/' | b'?' | b'!') => index + 2,
+            _ => index + 1,
+        };
+        tokens.push(TokenKind::Plain, index, name_start);
+        let name_end = text[name_start..]
+            .find(|character: char| character.is_whitespace() || matches!(character, '>' | '/'))
+            .map_or(text.len(), |end| name_start + end);
+        tokens.push(TokenKind::Tag, name_start, name_end);
+        index = name_end;
+        while index < bytes.len() {
+            let (kind, end) = match bytes[index] {
+                b'>' => {
+                    tokens.push(TokenKind::Plain, index, index + 1);
+                    index += 1;
+                    break;
+                }
+                b'"' | b'\'' => (TokenKind::String, quoted_end(text, index)),
+                b'=' | b'/' | b'?' => (TokenKind::Plain, index + 1),
+                byte if byte.is_ascii_whitespace() => (TokenKind::Plain, index + 1),
+                _ => {
+                    let end = text[index..]
+                        .find(|character: char| {
+                            character.is_whitespace() || matches!(character, '=' | '>' | '/')
+                        })
+                        .map_or(text.len(), |end| index + end);
+                    (TokenKind::Attribute, end)
+                }
+            };
+            tokens.push(kind, index, end);
+            index = end;
+        }
+    }
+}
+
+/// Splits a formatted body into highlighted pieces, joined they give back the text.
+pub fn tokenize(text: &str, syntax: Syntax) -> Vec<(TokenKind, &str)> {
+    let mut tokens = Tokens {
+        text,
+        ranges: Vec::new(),
+    };
+    match syntax {
+        Syntax::Json => json_tokens(&mut tokens),
+        Syntax::Markup => markup_tokens(&mut tokens),
+        Syntax::Plain if !text.is_empty() => tokens.push(TokenKind::Plain, 0, text.len()),
+        Syntax::Plain => {}
+    }
+    tokens
+        .ranges
+        .into_iter()
+        .map(|(kind, start, end)| (kind, &text[start..end]))
+        .collect()
+}
+
+/// Colors a formatted body for the terminal, `colored` leaves it plain when colors are off.
+pub fn colorize(text: &str, syntax: Syntax) -> String {
+    tokenize(text, syntax)
+        .into_iter()
+        .map(|(kind, piece)| match kind {
+            TokenKind::Plain => piece.normal(),
+            TokenKind::Key | TokenKind::Tag => piece.blue().bold(),
+            TokenKind::String => piece.green(),
+            TokenKind::Number => piece.cyan(),
+            TokenKind::Boolean => piece.yellow(),
+            TokenKind::Null => piece.magenta(),
+            TokenKind::Attribute => piece.cyan(),
+            TokenKind::Comment => piece.bright_black(),
+        })
+        .map(|piece| piece.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{tokenize, Syntax, TokenKind};
+
+    #[test]
+    fn json_and_markup_are_split_into_tokens() {
+        let json =
+            "{\n  \"name\": \"Ann \\\"A\\\"\",\n  \"age\": -4.5e2,\n  \"ok\": [true, null]\n}";
+        let tokens = tokenize(json, Syntax::Json);
+        assert_eq!(
+            tokens.iter().map(|(_, piece)| *piece).collect::<String>(),
+            json
+        );
+        assert_eq!(
+            tokens
+                .into_iter()
+                .filter(|(kind, _)| *kind != TokenKind::Plain)
+                .collect::<Vec<_>>(),
+            vec![
+                (TokenKind::Key, "\"name\""),
+                (TokenKind::String, "\"Ann \\\"A\\\"\""),
+                (TokenKind::Key, "\"age\""),
+                (TokenKind::Number, "-4.5e2"),
+                (TokenKind::Key, "\"ok\""),
+                (TokenKind::Boolean, "true"),
+                (TokenKind::Null, "null"),
+            ]
+        );
+
+        let xml = r#"<user id="1" note='a>b'><!-- é --><name>Ann</name><br/></user>"#;
+        let tokens = tokenize(xml, Syntax::Markup);
+        assert_eq!(
+            tokens.iter().map(|(_, piece)| *piece).collect::<String>(),
+            xml
+        );
+        assert_eq!(
+            tokens
+                .into_iter()
+                .filter(|(kind, _)| *kind != TokenKind::Plain)
+                .collect::<Vec<_>>(),
+            vec![
+                (TokenKind::Tag, "user"),
+                (TokenKind::Attribute, "id"),
+                (TokenKind::String, "\"1\""),
+                (TokenKind::Attribute, "note"),
+                (TokenKind::String, "'a>b'"),
+                (TokenKind::Comment, "<!-- é -->"),
+                (TokenKind::Tag, "name"),
+                (TokenKind::Tag, "name"),
+                (TokenKind::Tag, "br"),
+                (TokenKind::Tag, "user"),
+            ]
+        );
+        assert_eq!(
+            Syntax::detect(Some("text/html; charset=utf-8"), ""),
+            Syntax::Markup
+        );
+        assert_eq!(Syntax::detect(None, " [1]"), Syntax::Json);
+    }
+}
@@ -1,28 +1,72 @@
-use bytes::Bytes;
-use serde_json::Value;
+use super::{
+    filter::Filter,
+    format::{content_type, format_body, head, FormattedBody},
+    highlight::{colorize, Syntax},
+    Printer,
+};
+use crate::{config::types::GemonOutputDetail, request::request_builder::GemonResponse};
 
-use super::Printer;
-
-pub struct TerminalPrinter {}
+pub struct TerminalPrinter {
+    detail: GemonOutputDetail,
+    filter: Option<Filter>,
+}
 
 impl TerminalPrinter {
     pub fn new() -> TerminalPrinter {
-        TerminalPrinter {}
+        TerminalPrinter {
+            detail: GemonOutputDetail::Body,
+            filter: None,
+        }
+    }
+
+    pub fn set_detail(&mut self, detail: GemonOutputDetail) {
+        self.detail = detail;
     }
 
-    pub fn print_string(&self, path: &str) -> Result<(), std::io::Error> {
-        let value: Value = serde_json::from_str(path)?;
-        let pretty_response = serde_json::to_string_pretty(&value)?;
-        println!("{}", pretty_response);
+    pub fn set_filter(&mut self, filter: Option<Filter>) {
+        self.filter = filter;
+    }
+
+    /// Prints a body the way its content type asks for, binary bodies only get a summary. With
+    /// a filter only the values it picks out of a JSON body are printed. JSON, XML and HTML
+    /// are highlighted when colors are on.
+    pub fn print_bytes(
+        &self,
+        bytes: &[u8],
+        content_type: Option<&str>,
+    ) -> Result<(), std::io::Error> {
+        let formatted = match &self.filter {
+            Some(filter) => filter.format(bytes).map_err(std::io::Error::other)?,
+            None => format_body(bytes, content_type),
+        };
+        match formatted {
+            FormattedBody::Empty => {}
+            FormattedBody::Text(text) => {
+                let syntax = match self.filter {
+                    Some(_) => Syntax::Json,
+                    None => Syntax::detect(content_type, &text),
+                };
+                println!("{}", colorize(&text, syntax))
+            }
+            binary => println!(
+                "{}, save it with -rf=(path) to keep the content",
+                binary.summary()
+            ),
+        }
         Ok(())
     }
 }
 
 impl Printer for TerminalPrinter {
-    fn print(&self, bytes: &Bytes) -> Result<(), std::io::Error> {
-        let response_value: Value = serde_json::from_slice(bytes)?;
-        let pretty_response = serde_json::to_string_pretty(&response_value)?;
-        println!("{}", pretty_response);
-        Ok(())
+    /// Prints the status line and headers first when they were asked for, separated from the
+    /// body by an empty line.
+    fn print(&self, response: &GemonResponse) -> Result<(), std::io::Error> {
+        if let Some(head) = head(response, self.detail) {
+            println!("{head}");
+            if !response.data().is_empty() {
+                println!();
+            }
+        }
+        self.print_bytes(response.data(), content_type(response.headers()))
     }
 }
@@ -1,21 +1,47 @@
 use self::project_handler::{
-    add_env_value, delete_request, get_request, get_selected_env, print_all_env,
-    print_selected_env, remove_env, remove_env_value, save_request, set_selected_env,
+    add_env_value, authorization, delete_request, get_request, get_selected_env, new_project_root,
+    print_all_env, print_selected_env, project_path, remove_env, remove_env_value,
+    resolve_saved_rest_request, save_request, set_selected_env, stored_path,
 };
 use crate::{
-    command::GemonCommand,
     config::{types::GemonProjectScenario, GemonConfig},
     constants::{NO_ENV, PROJECT_ROOT_FILE},
+    exit::ExitError,
     printer::terminal_printer::TerminalPrinter,
     project::project_handler::get_project,
-    request::{request_builder::RequestBuilder, Request},
+    request::{
+        curl::{from_curl, to_curl},
+        proxy::ProxySettings,
+        request_builder::RequestBuilder,
+        tls::TlsSettings,
+        Request,
+    },
     EmptyResult,
 };
-use project_handler::{add_authorization, remove_authorization};
+use assertions::run_tests;
+use colored::Colorize;
+use cookie_store::Cookie;
+use cookies::{clear_cookies, print_cookies};
+use postman::import_postman;
+use project_handler::{
+    add_authorization, add_proxy_setting, add_tls_setting, remove_authorization, remove_proxy,
+    remove_tls,
+};
+use runner::run_collection;
 use serde_derive::{Deserialize, Serialize};
-use std::{collections::HashMap, error::Error, fmt, fs, io::stdin};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt, fs,
+    io::{self, stdin, IsTerminal, Read},
+    path::Path,
+};
 
+pub mod assertions;
+pub mod cookies;
+pub mod postman;
 pub mod project_handler;
+pub mod runner;
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Environment {
@@ -49,14 +75,60 @@ impl Environment {
     }
 }
 
+/// Why reading or changing the project failed, shown as is by the CLI and the TUI status line.
 #[derive(Debug)]
-pub struct ProjectError {
-    pub message: String,
+pub enum ProjectError {
+    /// There is no gemon.json in the current folder or its parents.
+    NotAProject,
+    /// A saved request, an environment or a file that does not exist.
+    NotFound(String),
+    /// The path could not be read or written with the permissions of the user.
+    Permission(String),
+    /// A project or request file is not valid JSON or does not have the expected fields.
+    Parse {
+        file: String,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// Any other failure reading or writing the path.
+    Io {
+        path: String,
+        message: String,
+    },
+    Invalid(String),
 }
 
 impl fmt::Display for ProjectError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Error: {}", self.message)
+        match self {
+            ProjectError::NotAProject => write!(
+                f,
+                "No gemon project in this folder or its parents, run `gemon init` to create one"
+            ),
+            ProjectError::NotFound(what) => write!(f, "{what} not found"),
+            ProjectError::Permission(path) => write!(f, "Permission denied for {path}"),
+            // errors raised after reading the JSON have no position
+            ProjectError::Parse {
+                file,
+                line: 0,
+                message,
+                ..
+            } => write!(f, "Could not parse {file}: {message}"),
+            ProjectError::Parse {
+                file,
+                line,
+                column,
+                message,
+            } => write!(
+                f,
+                "Could not parse {file} at line {line}, column {column}: {message}"
+            ),
+            ProjectError::Io { path, message } => {
+                write!(f, "Could not access {path}: {message}")
+            }
+            ProjectError::Invalid(message) => write!(f, "{message}"),
+        }
     }
 }
 
@@ -64,9 +136,36 @@ impl Error for ProjectError {}
 
 impl ProjectError {
     pub fn from(message: &str) -> Box<Self> {
-        Box::new(ProjectError {
-            message: String::from(message),
-        })
+        Box::new(ProjectError::Invalid(String::from(message)))
+    }
+
+    /// Error of reading or writing `path`.
+    pub fn io(path: impl AsRef<Path>, err: io::Error) -> ProjectError {
+        let path = path.as_ref().display();
+        match err.kind() {
+            io::ErrorKind::NotFound => ProjectError::NotFound(path.to_string()),
+            io::ErrorKind::PermissionDenied => ProjectError::Permission(path.to_string()),
+            _ => ProjectError::Io {
+                path: path.to_string(),
+                message: err.to_string(),
+            },
+        }
+    }
+
+    /// Error of parsing the JSON of `file`.
+    pub fn parse(file: impl AsRef<Path>, err: serde_json::Error) -> ProjectError {
+        // serde_json adds the position to the message of a data error itself
+        let message = err.to_string();
+        let message = match message.rfind(" at line ") {
+            Some(end) => message[..end].to_string(),
+            None => message,
+        };
+        ProjectError::Parse {
+            file: file.as_ref().display().to_string(),
+            line: err.line(),
+            column: err.column(),
+            message,
+        }
     }
 }
 
@@ -76,15 +175,20 @@ pub struct Project {
     selected_environment: Option<String>,
     environments: HashMap<String, Environment>,
     authorization: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    tls: HashMap<String, TlsSettings>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    proxy: HashMap<String, ProxySettings>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    cookies: HashMap<String, Vec<Cookie<'static>>>,
     last_called_request_path: Option<String>,
 }
 
 impl Project {
     pub fn init_named(name: &str) -> EmptyResult {
-        if get_project().is_some() {
-            return Err(Box::new(ProjectError {
-                message: String::from("Project already exists"),
-            }));
+        let path = new_project_root().join(PROJECT_ROOT_FILE);
+        if path.exists() {
+            return Err(ProjectError::from("Project already exists"));
         }
 
         let project = Project {
@@ -93,8 +197,11 @@ impl Project {
             environments: HashMap::new(),
             last_called_request_path: None,
             authorization: HashMap::new(),
+            tls: HashMap::new(),
+            proxy: HashMap::new(),
+            cookies: HashMap::new(),
         };
-        project.save()
+        project.write(&path)
     }
 
     pub fn name(&self) -> &str {
@@ -113,6 +220,14 @@ impl Project {
         &self.authorization
     }
 
+    pub fn tls_entries(&self) -> &HashMap<String, TlsSettings> {
+        &self.tls
+    }
+
+    pub fn cookie_entries(&self) -> &HashMap<String, Vec<Cookie<'static>>> {
+        &self.cookies
+    }
+
     pub fn last_called_request_path(&self) -> Option<&str> {
         self.last_called_request_path.as_deref()
     }
@@ -144,6 +259,9 @@ impl Project {
             self.selected_environment = None;
         }
         self.authorization.remove_entry(env);
+        self.tls.remove_entry(env);
+        self.proxy.remove_entry(env);
+        self.cookies.remove_entry(env);
     }
 
     fn set_selected_env(&mut self, env: &String) -> EmptyResult {
@@ -162,8 +280,20 @@ impl Project {
     }
 
     fn save(&self) -> EmptyResult {
+        self.write(&project_path(PROJECT_ROOT_FILE)?)
+    }
+
+    fn write(&self, path: &Path) -> EmptyResult {
         let project_str = serde_json::to_string_pretty(&self)?;
-        fs::write(PROJECT_ROOT_FILE, project_str).map_err(|err| err.into())
+        fs::write(path, project_str).map_err(|err| ProjectError::io(path, err).into())
+    }
+
+    /// The selected environment, or the key settings without an environment are stored under.
+    fn env_key(&self) -> String {
+        match &self.selected_environment {
+            Some(e) => e.to_owned(),
+            None => String::from(NO_ENV),
+        }
     }
 
     pub fn env() -> Option<Environment> {
@@ -171,101 +301,234 @@ impl Project {
     }
 
     pub fn authorization(&self) -> Option<&String> {
-        let env = match &self.selected_environment {
-            Some(e) => e.to_owned(),
-            None => String::from(NO_ENV),
-        };
+        let env = self.env_key();
         self.authorization.get(&env)
     }
 
     pub fn set_authorization(&mut self, authorization: &String) -> EmptyResult {
-        let env = match &self.selected_environment {
-            Some(e) => e.to_owned(),
-            None => String::from(NO_ENV),
-        };
+        let env = self.env_key();
         self.authorization.insert(env, authorization.into());
         Ok(())
     }
 
     pub fn remove_authorization(&mut self) -> EmptyResult {
-        let env = match &self.selected_environment {
-            Some(e) => e.to_owned(),
-            None => String::from(NO_ENV),
-        };
+        let env = self.env_key();
         self.authorization.remove_entry(&env);
         Ok(())
     }
 
+    pub fn tls(&self) -> Option<&TlsSettings> {
+        let env = self.env_key();
+        self.tls.get(&env)
+    }
+
+    pub fn set_tls_setting(&mut self, setting: &str, value: &str) -> EmptyResult {
+        let env = self.env_key();
+        self.tls
+            .entry(env)
+            .or_default()
+            .set(setting, value)
+            .map_err(|message| ProjectError::from(&message))?;
+        Ok(())
+    }
+
+    pub fn remove_tls(&mut self) -> EmptyResult {
+        let env = self.env_key();
+        self.tls.remove_entry(&env);
+        Ok(())
+    }
+
+    pub fn proxy(&self) -> Option<&ProxySettings> {
+        let env = self.env_key();
+        self.proxy.get(&env)
+    }
+
+    pub fn set_proxy_setting(&mut self, setting: &str, value: &str) -> EmptyResult {
+        let env = self.env_key();
+        self.proxy
+            .entry(env)
+            .or_default()
+            .set(setting, value)
+            .map_err(|message| ProjectError::from(&message))?;
+        Ok(())
+    }
+
+    pub fn remove_proxy(&mut self) -> EmptyResult {
+        let env = self.env_key();
+        self.proxy.remove_entry(&env);
+        Ok(())
+    }
+
+    pub fn cookies(&self) -> &[Cookie<'static>] {
+        let env = self.env_key();
+        self.cookies
+            .get(&env)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    fn set_cookies(&mut self, cookies: Vec<Cookie<'static>>) {
+        let env = self.env_key();
+        if cookies.is_empty() {
+            self.cookies.remove_entry(&env);
+        } else {
+            self.cookies.insert(env, cookies);
+        }
+    }
+
+    fn clear_cookies(&mut self, env: Option<&str>) {
+        let env = env.map_or_else(|| self.env_key(), str::to_owned);
+        self.cookies.remove_entry(&env);
+    }
+
     pub async fn execute(config: &GemonConfig, scenario: &GemonProjectScenario) -> EmptyResult {
         match scenario {
             GemonProjectScenario::Init => Project::init(),
             GemonProjectScenario::Call(name) => {
-                Request::call(get_request(name), config).await?;
-                Project::update_last_request_path(config.gemon_response_file_path())?;
-                Ok(())
+                let mut request = get_request(name)?;
+                request.apply_overrides(config)?;
+                let called = Request::call(request, config).await;
+                // A response failing the status check was still written
+                let responded = match &called {
+                    Ok(()) => true,
+                    Err(err) => matches!(err.downcast_ref(), Some(ExitError::HttpStatus(_))),
+                };
+                if responded {
+                    Project::update_last_request_path(config.gemon_response_file_path())?;
+                }
+                called
             }
             GemonProjectScenario::Save(name) => {
-                let request = RequestBuilder::build(config);
-                save_request(request, name);
+                let request = RequestBuilder::build(config)?;
+                save_request(request, name)?;
                 Ok(())
             }
             GemonProjectScenario::SaveAndCall(name) => {
-                let request = RequestBuilder::build(config);
-                Request::call(save_request(request, name), config).await
+                let request = RequestBuilder::build(config)?;
+                Request::call(save_request(request, name)?, config).await
             }
             GemonProjectScenario::Delete(name) => delete_request(name),
-            GemonProjectScenario::PrintLastCall => Project::print_last_called_request(),
+            GemonProjectScenario::PrintLastCall => Project::print_last_called_request(config),
+            GemonProjectScenario::Test(names) => run_tests(names).await,
+            GemonProjectScenario::Run(name) => run_collection(name).await,
+            GemonProjectScenario::ImportPostman(path) => import_postman(path),
+            GemonProjectScenario::ExportCurl(name) => Project::export_curl(config, name),
+            GemonProjectScenario::ImportCurl(name) => Project::import_curl(name),
             GemonProjectScenario::AddEnv(e, k, v) => add_env_value(e, (k.to_owned(), v.to_owned())),
             GemonProjectScenario::RemoveEnvValue(e, k) => remove_env_value(e, k),
             GemonProjectScenario::SelectEnv(e) => set_selected_env(e),
             GemonProjectScenario::PrintEnvAll => print_all_env(),
             GemonProjectScenario::PrintEnv => print_selected_env(),
             GemonProjectScenario::RemoveEnv(e) => remove_env(e),
-            GemonProjectScenario::Help => GemonCommand::print_all(),
             GemonProjectScenario::RemoveAuthorization => remove_authorization(),
             GemonProjectScenario::AddAuthorization(authorization) => {
                 add_authorization(authorization)
             }
+            GemonProjectScenario::AddTlsSetting(setting, value) => add_tls_setting(setting, value),
+            GemonProjectScenario::RemoveTls => remove_tls(),
+            GemonProjectScenario::AddProxySetting(setting, value) => {
+                add_proxy_setting(setting, value)
+            }
+            GemonProjectScenario::RemoveProxy => remove_proxy(),
+            GemonProjectScenario::PrintCookies => print_cookies(),
+            GemonProjectScenario::ClearCookies => clear_cookies(None),
         }
     }
 
     fn init() -> EmptyResult {
         println!("Provide the name of the project:");
         let mut name = String::new();
-        stdin()
-            .read_line(&mut name)
-            .expect("Failed to read project name");
+        stdin().read_line(&mut name)?;
         Project::init_named(&name)
     }
 
+    fn export_curl(config: &GemonConfig, name: &str) -> EmptyResult {
+        let request = resolve_saved_rest_request(name)?;
+        let authorization = if config.gemon_secure() {
+            authorization()
+        } else {
+            None
+        };
+        let mut command = to_curl(&request, authorization.as_deref());
+        if request.binary_body().is_some() {
+            let body_file = project_path(name)?.join("body.json");
+            let body_file = shell_words::quote(&body_file.to_string_lossy()).to_string();
+            command.push_str(&format!(" \\\n  --data-binary @{body_file}"));
+        }
+        println!("{command}");
+        Ok(())
+    }
+
+    fn import_curl(name: &String) -> EmptyResult {
+        if stdin().is_terminal() {
+            println!("Paste the curl command and finish with Ctrl-D:");
+        }
+        let mut command = String::new();
+        stdin().read_to_string(&mut command)?;
+        let import = from_curl(&command)?;
+        save_request(Box::new(import.request), name)?;
+        for option in import.ignored {
+            println!("{} {}", "Ignored".yellow().bold(), option);
+        }
+        println!("{} {}", "Saved request".green(), name);
+        Ok(())
+    }
+
     fn update_last_request_path(path: Option<String>) -> Result<(), Box<dyn Error>> {
-        let mut project = get_project().ok_or_else(|| {
-            Box::new(ProjectError {
-                message: String::from("Project could not be found!"),
-            })
-        })?;
-        if path.is_none() {
+        let mut project = get_project()?;
+        let Some(path) = path else {
             return Ok(());
-        }
-        project.set_last_called_request_path(path);
-        let project_str = serde_json::to_string_pretty(&project)?;
-        fs::write(PROJECT_ROOT_FILE, project_str).map_err(|err| err.into())
-    }
-
-    fn print_last_called_request() -> Result<(), Box<dyn Error>> {
-        let project = get_project().ok_or_else(|| {
-            Box::new(ProjectError {
-                message: String::from("Project could not be found!"),
-            })
-        })?;
-        let path = project.get_last_called_request_path().ok_or_else(|| {
-            Box::new(ProjectError {
-                message: String::from("No available response to print!"),
-            })
-        })?;
-        let value = fs::read_to_string(path)?;
-        TerminalPrinter::new()
-            .print_string(&value)
-            .map_err(|err| err.into())
+        };
+        project.set_last_called_request_path(Some(stored_path(&path)?));
+        project.save()
+    }
+
+    fn print_last_called_request(config: &GemonConfig) -> Result<(), Box<dyn Error>> {
+        let project = get_project()?;
+        let path = project
+            .get_last_called_request_path()
+            .ok_or_else(|| ProjectError::from("No available response to print!"))?;
+        let path = project_path(path)?;
+        let bytes = fs::read(&path).map_err(|err| ProjectError::io(&path, err))?;
+        let mut printer = TerminalPrinter::new();
+        printer.set_filter(config.gemon_filter().cloned());
+        printer.print_bytes(&bytes, None).map_err(|err| err.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Project, ProjectError};
+    use std::io;
+
+    #[test]
+    fn project_errors_point_at_the_cause() {
+        let content = "{\n  \"name\": \"shop\",\n  \"environments\": ]\n}";
+        let err = serde_json::from_str::<Project>(content).err().unwrap();
+        let err = ProjectError::parse("gemon.json", err);
+        assert!(matches!(
+            err,
+            ProjectError::Parse {
+                line: 3,
+                column: 19,
+                ..
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "Could not parse gemon.json at line 3, column 19: expected value"
+        );
+
+        let denied = io::Error::from(io::ErrorKind::PermissionDenied);
+        assert_eq!(
+            ProjectError::io("login/body.json", denied).to_string(),
+            "Permission denied for login/body.json"
+        );
+        let missing = io::Error::from(io::ErrorKind::NotFound);
+        assert!(matches!(
+            ProjectError::io("login/.marker", missing),
+            ProjectError::NotFound(path) if path == "login/.marker"
+        ));
+        assert!(ProjectError::NotAProject.to_string().contains("gemon init"));
     }
 }
@@ -0,0 +1,348 @@
+use super::project_handler::{get_request, list_saved_requests, project_path};
+use crate::{
+    exit::ExitError,
+    request::request_builder::{GemonRequest, GemonResponse},
+    EmptyResult,
+};
+use colored::Colorize;
+use regex::Regex;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{error::Error, fs, time::Instant};
+
+pub static ASSERTIONS_FILE: &str = "assertions.json";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct HeaderAssertion {
+    pub name: String,
+    #[serde(default)]
+    pub equals: Option<String>,
+}
+
+/// A check against the JSON response body. `path` is a JSON pointer such as `/data/0/id`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct JsonAssertion {
+    pub path: String,
+    #[serde(default)]
+    pub exists: Option<bool>,
+    #[serde(default)]
+    pub equals: Option<Value>,
+    #[serde(default)]
+    pub matches: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct RequestAssertions {
+    #[serde(default)]
+    pub status: Option<u16>,
+    #[serde(default)]
+    pub headers: Vec<HeaderAssertion>,
+    #[serde(default)]
+    pub json: Vec<JsonAssertion>,
+    #[serde(default)]
+    pub max_duration_ms: Option<u128>,
+}
+
+impl RequestAssertions {
+    pub fn read(name: &str) -> Result<Option<RequestAssertions>, Box<dyn Error>> {
+        match fs::read_to_string(project_path(name)?.join(ASSERTIONS_FILE)) {
+            Ok(content) => Ok(Some(serde_json::from_str(&content)?)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Whether the saved request has an `assertions.json`.
+    pub fn exists(name: &str) -> bool {
+        project_path(name).is_ok_and(|path| path.join(ASSERTIONS_FILE).is_file())
+    }
+
+    /// Returns a description of every assertion that did not hold.
+    pub fn evaluate(&self, response: &GemonResponse, elapsed_ms: u128) -> Vec<String> {
+        let mut failures = Vec::new();
+
+        if let Some(status) = self.status {
+            if response.status() != status {
+                failures.push(format!(
+                    "expected status {status}, got {}",
+                    response.status()
+                ));
+            }
+        }
+
+        for header in &self.headers {
+            let value = response
+                .headers()
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(&header.name))
+                .map(|(_, value)| value);
+            match (value, header.equals.as_ref()) {
+                (None, _) => failures.push(format!("header '{}' is missing", header.name)),
+                (Some(actual), Some(expected)) if actual != expected => failures.push(format!(
+                    "header '{}' expected '{expected}', got '{actual}'",
+                    header.name
+                )),
+                _ => {}
+            }
+        }
+
+        if !self.json.is_empty() {
+            match serde_json::from_slice::<Value>(response.data()) {
+                Ok(body) => self
+                    .json
+                    .iter()
+                    .for_each(|assertion| failures.extend(assertion.evaluate(&body))),
+                Err(_) => failures.push(String::from("response body is not valid JSON")),
+            }
+        }
+
+        if let Some(max_duration_ms) = self.max_duration_ms {
+            if elapsed_ms > max_duration_ms {
+                failures.push(format!(
+                    "took {elapsed_ms} ms, more than the allowed {max_duration_ms} ms"
+                ));
+            }
+        }
+
+        failures
+    }
+}
+
+impl JsonAssertion {
+    fn evaluate(&self, body: &Value) -> Vec<String> {
+        let mut failures = Vec::new();
+        let value = body.pointer(&self.path);
+
+        if let Some(exists) = self.exists {
+            if value.is_some() != exists {
+                failures.push(if exists {
+                    format!("'{}' does not exist", self.path)
+                } else {
+                    format!("'{}' exists", self.path)
+                });
+            }
+        }
+
+        if let Some(expected) = self.equals.as_ref() {
+            if value != Some(expected) {
+                failures.push(format!(
+                    "'{}' expected {expected}, got {}",
+                    self.path,
+                    value
+                        .map(Value::to_string)
+                        .unwrap_or(String::from("nothing"))
+                ));
+            }
+        }
+
+        if let Some(pattern) = self.matches.as_ref() {
+            let text = match value {
+                Some(Value::String(text)) => Some(text.to_owned()),
+                Some(other) => Some(other.to_string()),
+                None => None,
+            };
+            match (Regex::new(pattern), text) {
+                (Err(err), _) => failures.push(format!("invalid regex '{pattern}': {err}")),
+                (Ok(_), None) => failures.push(format!("'{}' does not exist", self.path)),
+                (Ok(regex), Some(text)) if !regex.is_match(&text) => failures.push(format!(
+                    "'{}' value '{text}' does not match '{pattern}'",
+                    self.path
+                )),
+                _ => {}
+            }
+        }
+
+        failures
+    }
+}
+
+/// The outcome of calling a saved request and checking it against its `assertions.json`.
+pub struct RequestCheck {
+    pub elapsed_ms: u128,
+    pub has_assertions: bool,
+    pub response: Option<GemonResponse>,
+    pub failures: Vec<String>,
+}
+
+impl RequestCheck {
+    fn failed(message: String) -> RequestCheck {
+        RequestCheck {
+            elapsed_ms: 0,
+            has_assertions: false,
+            response: None,
+            failures: vec![message],
+        }
+    }
+}
+
+/// Loads the saved request, calls it and evaluates its assertions against the response.
+pub async fn check_request(name: &str) -> RequestCheck {
+    let assertions = match RequestAssertions::read(name) {
+        Ok(assertions) => assertions,
+        Err(err) => return RequestCheck::failed(format!("invalid {ASSERTIONS_FILE}: {err}")),
+    };
+
+    let request = match get_request(name) {
+        Ok(request) => request,
+        Err(err) => return RequestCheck::failed(err.to_string()),
+    };
+    let started = Instant::now();
+    let response = request.execute().await;
+    let elapsed_ms = started.elapsed().as_millis();
+
+    let mut check = RequestCheck {
+        elapsed_ms,
+        has_assertions: assertions.is_some(),
+        response: None,
+        failures: Vec::new(),
+    };
+    match response {
+        Ok(response) => {
+            if let Some(assertions) = assertions {
+                check.failures = assertions.evaluate(&response, elapsed_ms);
+            }
+            check.response = Some(response);
+        }
+        Err(err) => check.failures.push(format!("request failed: {err}")),
+    }
+    check
+}
+
+/// Calls the named saved requests (or all of those with an `assertions.json`), checks their
+/// assertions and fails when any of them did not pass.
+pub async fn run_tests(names: &[String]) -> EmptyResult {
+    let saved = list_saved_requests()?
+        .into_iter()
+        .map(|request| request.name)
+        .collect::<Vec<_>>();
+    // Without names only the requests that describe their expected response are called, so
+    // side-effecting requests without assertions are never sent by accident.
+    let names = if names.is_empty() {
+        saved
+            .iter()
+            .filter(|name| RequestAssertions::exists(name))
+            .cloned()
+            .collect()
+    } else {
+        names.to_vec()
+    };
+    if names.is_empty() {
+        return Err(Box::new(ExitError::Assertion(format!(
+            "No saved request has an {ASSERTIONS_FILE}, name the requests to test"
+        ))));
+    }
+
+    let mut failed = 0;
+    for name in &names {
+        let outcome = if saved.contains(name) {
+            check_request(name).await
+        } else {
+            RequestCheck::failed(String::from("saved request not found"))
+        };
+
+        let timing = format!("({} ms)", outcome.elapsed_ms);
+        if outcome.failures.is_empty() {
+            let note = if outcome.has_assertions {
+                ""
+            } else {
+                " no assertions"
+            };
+            println!(
+                "{} {} {}{}",
+                "PASS".green().bold(),
+                name,
+                timing.dimmed(),
+                note.yellow()
+            );
+        } else {
+            failed += 1;
+            println!("{} {} {}", "FAIL".red().bold(), name, timing.dimmed());
+            for failure in &outcome.failures {
+                println!("     {}", failure.red());
+            }
+        }
+    }
+
+    let summary = format!("{} passed, {failed} failed", names.len() - failed);
+    if failed == 0 {
+        println!("{}", summary.green().bold());
+        Ok(())
+    } else {
+        println!("{}", summary.red().bold());
+        Err(Box::new(ExitError::Assertion(format!(
+            "{failed} of {} requests failed",
+            names.len()
+        ))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HeaderAssertion, JsonAssertion, RequestAssertions};
+    use crate::request::request_builder::GemonResponse;
+    use bytes::Bytes;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn response(status: u16, body: &str) -> GemonResponse {
+        let mut headers = HashMap::new();
+        headers.insert(
+            String::from("content-type"),
+            String::from("application/json"),
+        );
+        GemonResponse::new(Bytes::from(body.to_string()), status, headers)
+    }
+
+    #[test]
+    fn passing_assertions_report_no_failures() {
+        let assertions = RequestAssertions {
+            status: Some(200),
+            headers: vec![HeaderAssertion {
+                name: String::from("Content-Type"),
+                equals: Some(String::from("application/json")),
+            }],
+            json: vec![
+                JsonAssertion {
+                    path: String::from("/user/id"),
+                    equals: Some(json!(7)),
+                    ..JsonAssertion::default()
+                },
+                JsonAssertion {
+                    path: String::from("/user/email"),
+                    matches: Some(String::from("^.+@example\\.com$")),
+                    ..JsonAssertion::default()
+                },
+                JsonAssertion {
+                    path: String::from("/user/deleted"),
+                    exists: Some(false),
+                    ..JsonAssertion::default()
+                },
+            ],
+            max_duration_ms: Some(100),
+        };
+        let response = response(200, r#"{"user": {"id": 7, "email": "a@example.com"}}"#);
+
+        assert!(assertions.evaluate(&response, 20).is_empty());
+    }
+
+    #[test]
+    fn failing_assertions_are_all_reported() {
+        let assertions = RequestAssertions {
+            status: Some(200),
+            headers: vec![HeaderAssertion {
+                name: String::from("x-request-id"),
+                equals: None,
+            }],
+            json: vec![JsonAssertion {
+                path: String::from("/id"),
+                exists: Some(true),
+                ..JsonAssertion::default()
+            }],
+            max_duration_ms: Some(10),
+        };
+
+        let failures = assertions.evaluate(&response(500, "{}"), 20);
+
+        assert_eq!(failures.len(), 4);
+    }
+}
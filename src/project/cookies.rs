@@ -0,0 +1,100 @@
+use super::project_handler::get_project;
+use crate::EmptyResult;
+use cookie_store::{Cookie, CookieExpiration, CookieStore};
+use serde_derive::Serialize;
+
+/// A stored cookie in the shape it is printed and shown in the TUI.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct CookieInfo {
+    pub name: String,
+    pub value: String,
+    pub domain: String,
+    pub path: String,
+    /// Expiration time, `None` for session cookies.
+    pub expires: Option<String>,
+}
+
+impl CookieInfo {
+    pub fn from(cookie: &Cookie<'static>) -> CookieInfo {
+        CookieInfo {
+            name: cookie.name().to_string(),
+            value: cookie.value().to_string(),
+            domain: String::from(&cookie.domain),
+            path: String::from(&cookie.path),
+            expires: match &cookie.expires {
+                CookieExpiration::AtUtc(time) => Some(time.to_string()),
+                CookieExpiration::SessionEnd => None,
+            },
+        }
+    }
+}
+
+/// Cookie jar of the selected environment, empty when there is no project.
+pub fn load_cookie_jar() -> CookieStore {
+    let cookies = get_project()
+        .ok()
+        .map(|project| project.cookies().to_vec())
+        .unwrap_or_default();
+    CookieStore::from_cookies(cookies.into_iter().map(Ok::<_, ()>), false).unwrap_or_default()
+}
+
+/// Stores the unexpired cookies of `jar`, session cookies included, for the selected
+/// environment. Nothing is written when there is no project or the cookies did not change.
+pub fn save_cookie_jar(jar: &CookieStore) -> EmptyResult {
+    let Ok(mut project) = get_project() else {
+        return Ok(());
+    };
+    let cookies = jar.iter_unexpired().cloned().collect::<Vec<_>>();
+    if cookies == project.cookies() {
+        return Ok(());
+    }
+    project.set_cookies(cookies);
+    project.save()
+}
+
+pub fn print_cookies() -> EmptyResult {
+    let project = get_project()?;
+    let cookies = project
+        .cookies()
+        .iter()
+        .map(CookieInfo::from)
+        .collect::<Vec<_>>();
+    println!("{}", serde_json::to_string_pretty(&cookies)?);
+    Ok(())
+}
+
+/// Removes the cookies of the given environment, or of the selected one when `env` is `None`.
+pub fn clear_cookies(env: Option<&str>) -> EmptyResult {
+    let mut project = get_project()?;
+    project.clear_cookies(env);
+    project.save()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CookieInfo;
+    use cookie_store::CookieStore;
+    use reqwest::Url;
+
+    #[test]
+    fn session_and_persistent_cookies_are_described() {
+        let url = Url::parse("https://api.example.com/auth/login").unwrap();
+        let mut jar = CookieStore::default();
+        jar.parse("session=abc; Path=/; HttpOnly", &url).unwrap();
+        jar.parse("theme=dark; Max-Age=3600", &url).unwrap();
+
+        let mut cookies = jar
+            .iter_unexpired()
+            .map(CookieInfo::from)
+            .collect::<Vec<_>>();
+        cookies.sort_by(|left, right| left.name.cmp(&right.name));
+
+        assert_eq!(cookies[0].name, "session");
+        assert_eq!(cookies[0].value, "abc");
+        assert_eq!(cookies[0].domain, "api.example.com");
+        assert_eq!(cookies[0].path, "/");
+        assert_eq!(cookies[0].expires, None);
+        assert_eq!(cookies[1].path, "/auth");
+        assert!(cookies[1].expires.is_some());
+    }
+}
@@ -0,0 +1,616 @@
+use super::{
+    project_handler::{add_env_value, get_project, list_saved_requests, save_request},
+    ProjectError,
+};
+use crate::{
+    config::types::{GemonBodyType, GemonMethodType},
+    request::{
+        multipart,
+        rest_request::{GemonRestRequest, GemonRestRequestBuilder},
+    },
+    EmptyResult,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use colored::Colorize;
+use regex::Regex;
+use serde_json::Value;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    sync::OnceLock,
+};
+
+#[derive(Debug, Default)]
+struct PostmanImport {
+    requests: Vec<(String, GemonRestRequest)>,
+    environments: Vec<(String, Vec<(String, String)>)>,
+    warnings: Vec<String>,
+    /// Saved requests of the project and the ones imported so far.
+    taken_names: HashSet<String>,
+}
+
+fn variable_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r"\{\{\s*([^{}]+?)\s*\}\}").expect("Invalid variable regex"))
+}
+
+/// Rewrites Postman `{{var}}` placeholders into Gemon `{var}` placeholders.
+fn convert_variables(text: &str) -> String {
+    variable_regex().replace_all(text, "{$1}").to_string()
+}
+
+fn convert_pairs(pairs: HashMap<String, String>) -> HashMap<String, String> {
+    pairs
+        .into_iter()
+        .map(|(key, value)| (convert_variables(&key), convert_variables(&value)))
+        .collect()
+}
+
+fn has_dynamic_variable(text: &str) -> bool {
+    variable_regex()
+        .captures_iter(text)
+        .any(|captures| captures[1].starts_with('$'))
+}
+
+/// Keeps names usable as directory names: anything besides letters, digits, `-`, `_` and `.`
+/// becomes a `-`, and leading dots are dropped so the folder is not hidden.
+fn sanitize_name(name: &str) -> String {
+    let mut sanitized = String::new();
+    for c in name.trim().chars() {
+        if c.is_alphanumeric() || c == '_' || c == '.' {
+            sanitized.push(c);
+        } else if !sanitized.ends_with('-') {
+            sanitized.push('-');
+        }
+    }
+    sanitized
+        .trim_start_matches(['-', '.'])
+        .trim_end_matches('-')
+        .to_string()
+}
+
+fn text<'a>(value: &'a Value, key: &str) -> Option<&'a str> {
+    value.get(key).and_then(Value::as_str)
+}
+
+fn is_disabled(value: &Value) -> bool {
+    value.get("disabled").and_then(Value::as_bool) == Some(true)
+        || value.get("enabled").and_then(Value::as_bool) == Some(false)
+}
+
+/// Values of an auth block are stored as `[{ "key": .., "value": .. }]` in v2.1.
+fn auth_value(auth: &Value, auth_type: &str, key: &str) -> Option<String> {
+    auth.get(auth_type)?
+        .as_array()?
+        .iter()
+        .find(|entry| text(entry, "key") == Some(key))
+        .and_then(|entry| entry.get("value"))
+        .map(|value| match value {
+            Value::String(text) => text.to_owned(),
+            other => other.to_string(),
+        })
+}
+
+impl PostmanImport {
+    fn warn(&mut self, name: &str, message: impl Into<String>) {
+        self.warnings.push(format!("{name}: {}", message.into()));
+    }
+
+    fn is_collection(&self, path: &str) -> bool {
+        let prefix = format!("{path}/");
+        self.taken_names
+            .iter()
+            .any(|name| name.starts_with(&prefix))
+    }
+
+    /// Appends a counter to the last part of `folders/name` until it doesn't clash with a saved
+    /// request, or with a collection when it names a request itself.
+    fn unique_path(&self, folders: &[String], name: &str, is_request: bool) -> String {
+        let name = if name.is_empty() { "request" } else { name };
+        let prefix = folders
+            .iter()
+            .map(|folder| format!("{folder}/"))
+            .collect::<String>();
+        let mut candidate = format!("{prefix}{name}");
+        let mut counter = 2;
+        while self.taken_names.contains(&candidate)
+            || (is_request && self.is_collection(&candidate))
+        {
+            candidate = format!("{prefix}{name}-{counter}");
+            counter += 1;
+        }
+        candidate
+    }
+
+    fn check_scripts(&mut self, name: &str, item: &Value) {
+        let scripts = item
+            .get("event")
+            .and_then(Value::as_array)
+            .map(|events| {
+                events
+                    .iter()
+                    .filter_map(|event| text(event, "listen"))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        if !scripts.is_empty() {
+            self.warn(
+                name,
+                format!("{} scripts were not imported", scripts.join(" and ")),
+            );
+        }
+    }
+
+    fn collection(&mut self, collection: &Value) {
+        let name = collection
+            .get("info")
+            .and_then(|info| text(info, "name"))
+            .unwrap_or("postman")
+            .to_string();
+        self.check_scripts(&name, collection);
+
+        let variables = self.variables(collection.get("variable"));
+        if !variables.is_empty() {
+            self.environments.push((sanitize_name(&name), variables));
+        }
+
+        if let Some(items) = collection.get("item").and_then(Value::as_array) {
+            self.items(items, &[], collection.get("auth"));
+        }
+    }
+
+    fn environment(&mut self, environment: &Value) {
+        let name = text(environment, "name").unwrap_or("postman");
+        let variables = self.variables(environment.get("values"));
+        self.environments.push((sanitize_name(name), variables));
+    }
+
+    fn variables(&mut self, variables: Option<&Value>) -> Vec<(String, String)> {
+        variables
+            .and_then(Value::as_array)
+            .map(|variables| {
+                variables
+                    .iter()
+                    .filter(|variable| !is_disabled(variable))
+                    .filter_map(|variable| {
+                        let key = text(variable, "key")?.to_string();
+                        let value = match variable.get("value") {
+                            Some(Value::String(text)) => convert_variables(text),
+                            Some(Value::Null) | None => String::new(),
+                            Some(other) => other.to_string(),
+                        };
+                        Some((key, value))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn items(&mut self, items: &[Value], folders: &[String], auth: Option<&Value>) {
+        for item in items {
+            let name = text(item, "name").unwrap_or_default();
+            let mut display_path = folders.to_vec();
+            display_path.push(name.to_string());
+            let display_name = display_path.join("/");
+            self.check_scripts(&display_name, item);
+
+            match item.get("item").and_then(Value::as_array) {
+                Some(children) => {
+                    // Folders become collections, unless the folder itself has no usable name.
+                    let mut path = folders.to_vec();
+                    let folder = sanitize_name(name);
+                    if !folder.is_empty() {
+                        let collection = self.unique_path(folders, &folder, false);
+                        path = collection.split('/').map(String::from).collect();
+                    }
+                    self.items(children, &path, item.get("auth").or(auth))
+                }
+                None => {
+                    if let Some(request) = self.request(&display_name, item, auth) {
+                        let name = self.unique_path(folders, &sanitize_name(name), true);
+                        self.taken_names.insert(name.clone());
+                        self.requests.push((name, request));
+                    }
+                }
+            }
+        }
+    }
+
+    fn request(
+        &mut self,
+        name: &str,
+        item: &Value,
+        inherited_auth: Option<&Value>,
+    ) -> Option<GemonRestRequest> {
+        let request = item.get("request")?;
+        if let Some(url) = request.as_str() {
+            return Some(
+                GemonRestRequestBuilder::new()
+                    .set_gemon_method_type(GemonMethodType::Get)
+                    .set_url(convert_variables(url))
+                    .build(),
+            );
+        }
+
+        let method = match text(request, "method").unwrap_or("GET").parse() {
+            Ok(method) => method,
+            Err(message) => {
+                self.warn(name, format!("{message}, skipped"));
+                return None;
+            }
+        };
+
+        let url = match request.get("url") {
+            Some(Value::String(url)) => Some(url.as_str()),
+            Some(url) => text(url, "raw"),
+            None => None,
+        };
+        let Some(mut url) = url.map(String::from) else {
+            self.warn(name, "request has no url, skipped");
+            return None;
+        };
+
+        let mut headers = HashMap::new();
+        for header in request
+            .get("header")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter(|header| !is_disabled(header))
+        {
+            if let (Some(key), Some(value)) = (text(header, "key"), text(header, "value")) {
+                headers.insert(key.to_string(), value.to_string());
+            }
+        }
+
+        let content_type = headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case("content-type"))
+            .and_then(|(_, value)| GemonBodyType::from_content_type(value));
+        let (body, form_data, body_type) = self.body(name, request.get("body"), content_type);
+
+        if let Some(auth) = request.get("auth").or(inherited_auth) {
+            self.auth(name, auth, &mut headers, &mut url);
+        }
+
+        let uses_dynamic_variables = [url.as_str(), body.as_deref().unwrap_or_default()]
+            .into_iter()
+            .chain(headers.values().map(String::as_str))
+            .chain(form_data.values().map(String::as_str))
+            .any(has_dynamic_variable);
+        if uses_dynamic_variables {
+            self.warn(
+                name,
+                "Postman dynamic variables such as {{$guid}} need to be replaced by hand",
+            );
+        }
+
+        Some(
+            GemonRestRequestBuilder::new()
+                .set_gemon_method_type(method)
+                .set_url(convert_variables(&url))
+                .set_headers(&convert_pairs(headers))
+                .set_body(body.map(|body| convert_variables(&body)))
+                .set_body_type(Some(body_type))
+                .set_form_data(&convert_pairs(form_data))
+                .build(),
+        )
+    }
+
+    /// Returns the body, the form fields and the body type. Raw bodies take their type from
+    /// the language picked in Postman, then from the Content-Type header and fall back to JSON.
+    fn body(
+        &mut self,
+        name: &str,
+        body: Option<&Value>,
+        content_type: Option<GemonBodyType>,
+    ) -> (Option<String>, HashMap<String, String>, GemonBodyType) {
+        let mut form_data = HashMap::new();
+        let Some(body) = body else {
+            return (None, form_data, GemonBodyType::None);
+        };
+
+        let mode = text(body, "mode").unwrap_or("raw");
+        let body_type = match mode {
+            "raw" => {
+                let language = body
+                    .pointer("/options/raw/language")
+                    .and_then(Value::as_str);
+                match language {
+                    Some("json") => GemonBodyType::Json,
+                    Some("xml") => GemonBodyType::Xml,
+                    Some(_) => GemonBodyType::Text,
+                    None => content_type.unwrap_or(GemonBodyType::Json),
+                }
+            }
+            "urlencoded" => GemonBodyType::FormUrlencoded,
+            "formdata" => GemonBodyType::Multipart,
+            "graphql" => GemonBodyType::Json,
+            _ => GemonBodyType::None,
+        };
+        let body_text = match mode {
+            "raw" => text(body, "raw").map(String::from),
+            "urlencoded" | "formdata" => {
+                let mode = text(body, "mode").unwrap_or_default();
+                for field in body
+                    .get(mode)
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                    .filter(|field| !is_disabled(field))
+                {
+                    let key = text(field, "key").unwrap_or_default();
+                    let value = if text(field, "type") == Some("file") {
+                        let src = match field.get("src") {
+                            Some(Value::Array(sources)) => sources.first().and_then(Value::as_str),
+                            Some(src) => src.as_str(),
+                            None => None,
+                        };
+                        let Some(src) = src.filter(|src| !src.is_empty()) else {
+                            self.warn(name, format!("file form field '{key}' has no file"));
+                            continue;
+                        };
+                        match text(field, "contentType").filter(|ct| !ct.is_empty()) {
+                            Some(content_type) => format!("@{src};type={content_type}"),
+                            None => format!("@{src}"),
+                        }
+                    } else {
+                        let value = text(field, "value").unwrap_or_default();
+                        match multipart::is_file(value) {
+                            true => format!("\\{value}"),
+                            false => value.to_string(),
+                        }
+                    };
+                    form_data.insert(key.to_string(), value);
+                }
+                None
+            }
+            "graphql" => body.get("graphql").map(|graphql| {
+                let variables = text(graphql, "variables")
+                    .and_then(|variables| serde_json::from_str::<Value>(variables).ok())
+                    .unwrap_or(Value::Null);
+                serde_json::json!({
+                    "query": text(graphql, "query").unwrap_or_default(),
+                    "variables": variables,
+                })
+                .to_string()
+            }),
+            mode => {
+                self.warn(name, format!("{mode} body was not imported"));
+                None
+            }
+        };
+        (body_text, form_data, body_type)
+    }
+
+    fn auth(
+        &mut self,
+        name: &str,
+        auth: &Value,
+        headers: &mut HashMap<String, String>,
+        url: &mut String,
+    ) {
+        match text(auth, "type").unwrap_or("noauth") {
+            "noauth" => {}
+            "bearer" => {
+                let token = auth_value(auth, "bearer", "token").unwrap_or_default();
+                headers.insert(String::from("Authorization"), format!("Bearer {token}"));
+            }
+            "basic" => {
+                let username = auth_value(auth, "basic", "username").unwrap_or_default();
+                let password = auth_value(auth, "basic", "password").unwrap_or_default();
+                let credentials = format!("{username}:{password}");
+                if credentials.contains("{{") {
+                    self.warn(
+                        name,
+                        "basic auth uses variables and cannot be encoded ahead of time, skipped",
+                    );
+                    return;
+                }
+                headers.insert(
+                    String::from("Authorization"),
+                    format!("Basic {}", STANDARD.encode(credentials)),
+                );
+            }
+            "apikey" => {
+                let key = auth_value(auth, "apikey", "key").unwrap_or_default();
+                let value = auth_value(auth, "apikey", "value").unwrap_or_default();
+                if auth_value(auth, "apikey", "in").as_deref() == Some("query") {
+                    let separator = if url.contains('?') { '&' } else { '?' };
+                    url.push_str(&format!("{separator}{key}={value}"));
+                } else {
+                    headers.insert(key, value);
+                }
+            }
+            auth_type => self.warn(name, format!("{auth_type} auth is not supported")),
+        }
+    }
+}
+
+/// Converts a Postman export, naming requests so that none of `saved` is overwritten.
+fn convert(document: &Value, saved: HashSet<String>) -> Result<PostmanImport, Box<ProjectError>> {
+    let mut import = PostmanImport {
+        taken_names: saved,
+        ..PostmanImport::default()
+    };
+    if document.get("info").is_some() && document.get("item").is_some() {
+        let schema = document
+            .get("info")
+            .and_then(|info| text(info, "schema"))
+            .unwrap_or_default();
+        if !schema.contains("v2.1") {
+            import.warn(
+                "collection",
+                "not a v2.1 collection, the import may be incomplete",
+            );
+        }
+        import.collection(document);
+    } else if document.get("values").is_some() {
+        import.environment(document);
+    } else {
+        return Err(ProjectError::from(
+            "File is neither a Postman v2.1 collection nor an environment export",
+        ));
+    }
+    Ok(import)
+}
+
+/// Imports a Postman v2.1 collection (or environment export) into the current project and
+/// prints everything that could not be translated.
+pub fn import_postman(path: &str) -> EmptyResult {
+    get_project()?;
+    let content = fs::read_to_string(path)
+        .map_err(|err| ProjectError::from(&format!("Could not read {path}: {err}")))?;
+    let document: Value = serde_json::from_str(&content)?;
+    let saved = list_saved_requests()?
+        .into_iter()
+        .map(|request| request.name)
+        .collect();
+    let import = convert(&document, saved)?;
+
+    for (name, request) in import.requests.iter() {
+        save_request(Box::new(request.clone()), name)?;
+        println!("{} {}", "Imported request".green(), name);
+    }
+    for (name, variables) in import.environments.iter() {
+        for (key, value) in variables {
+            add_env_value(name, (key.to_owned(), value.to_owned()))?;
+        }
+        println!("{} {}", "Imported environment".green(), name);
+    }
+    for warning in import.warnings.iter() {
+        println!("{} {}", "Not translated".yellow().bold(), warning);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{convert, convert_variables, sanitize_name};
+    use crate::{
+        config::types::{GemonBodyType, GemonMethodType},
+        request::request_builder::GemonRequest,
+    };
+    use serde_json::json;
+    use std::collections::HashSet;
+
+    #[test]
+    fn variables_use_gemon_placeholders() {
+        assert_eq!(
+            convert_variables("{{base_url}}/users/{{ id }}?q={{$guid}}"),
+            "{base_url}/users/{id}?q={$guid}"
+        );
+    }
+
+    #[test]
+    fn collections_keep_folders_and_report_untranslated_items() {
+        let collection = json!({
+            "info": {
+                "name": "Shop API",
+                "schema": "https://schema.getpostman.com/json/collection/v2.1.0/collection.json"
+            },
+            "auth": {"type": "bearer", "bearer": [{"key": "token", "value": "{{token}}"}]},
+            "variable": [{"key": "base_url", "value": "http://localhost"}],
+            "item": [{
+                "name": "Users",
+                "item": [{
+                    "name": "Upload avatar",
+                    "request": {
+                        "method": "POST",
+                        "body": {
+                            "mode": "formdata",
+                            "formdata": [
+                                {"key": "user", "value": "{{name}}"},
+                                {"key": "avatar", "type": "file", "src": ["/tmp/me.png"]}
+                            ]
+                        },
+                        "url": "{{base_url}}/avatars"
+                    }
+                }, {
+                    "name": "Create user",
+                    "event": [{"listen": "test", "script": {"exec": ["pm.test()"]}}],
+                    "request": {
+                        "method": "POST",
+                        "header": [
+                            {"key": "X-Trace", "value": "{{trace}}"},
+                            {"key": "X-Off", "value": "1", "disabled": true}
+                        ],
+                        "body": {"mode": "raw", "raw": "{\"name\": \"{{name}}\"}"},
+                        "url": {"raw": "{{base_url}}/users"}
+                    }
+                }, {
+                    "name": "Digest",
+                    "request": {
+                        "method": "GET",
+                        "auth": {"type": "digest"},
+                        "url": "{{base_url}}/digest"
+                    }
+                }]
+            }]
+        });
+
+        let import = convert(&collection, HashSet::new()).unwrap();
+
+        assert_eq!(import.requests.len(), 3);
+        let (_, upload) = &import.requests[0];
+        assert_eq!(upload.body_type(), GemonBodyType::Multipart);
+        assert_eq!(upload.form_data().get("user").unwrap(), "{name}");
+        assert_eq!(upload.form_data().get("avatar").unwrap(), "@/tmp/me.png");
+        let (name, request) = &import.requests[1];
+        assert_eq!(name, "Users/Create-user");
+        assert_eq!(request.method(), GemonMethodType::Post);
+        assert_eq!(request.uri(), "{base_url}/users");
+        assert_eq!(request.headers().get("X-Trace").unwrap(), "{trace}");
+        assert_eq!(
+            request.headers().get("Authorization").unwrap(),
+            "Bearer {token}"
+        );
+        assert!(!request.headers().contains_key("X-Off"));
+        assert_eq!(request.json_body(), "{\"name\": \"{name}\"}");
+        assert_eq!(request.body_type(), GemonBodyType::Json);
+        assert_eq!(
+            import.environments,
+            vec![(
+                String::from("Shop-API"),
+                vec![(String::from("base_url"), String::from("http://localhost"))]
+            )]
+        );
+        assert_eq!(import.warnings.len(), 2);
+    }
+
+    #[test]
+    fn imported_names_never_replace_saved_requests_or_collections() {
+        let request = |name: &str| json!({"name": name, "request": {"method": "GET", "url": "/"}});
+        let collection = json!({
+            "info": {"name": "Shop", "schema": "v2.1.0"},
+            "item": [
+                request("Login"),
+                {"name": "Users", "item": [request("List"), request("List")]},
+                request("Users"),
+                {"name": ".hidden", "item": [request("Profile")]},
+                {"name": "Admin", "item": [request("Audit")]}
+            ]
+        });
+        let saved = HashSet::from([String::from("Login"), String::from("Admin")]);
+
+        let import = convert(&collection, saved).unwrap();
+
+        let names = import
+            .requests
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                "Login-2",
+                "Users/List",
+                "Users/List-2",
+                "Users-2",
+                "hidden/Profile",
+                "Admin-2/Audit"
+            ]
+        );
+        assert_eq!(sanitize_name("../etc"), "etc");
+    }
+}
@@ -1,14 +1,31 @@
 use super::{Environment, Project, ProjectError};
 use crate::{
     config::effector::Effector,
-    constants::PROJECT_ROOT_FILE,
+    constants::{PROJECT_ENV, PROJECT_ROOT_FILE},
     request::{
-        request_builder::{GemonRequest, RequestBuilder},
+        proxy::ProxySettings,
+        request_builder::{AnyGemonRequest, GemonRequest, RequestBuilder},
         rest_request::GemonRestRequest,
+        tls::TlsSettings,
+        websocket_request::GemonWebsocketRequest,
     },
     EmptyResult,
 };
-use std::{error::Error, fs};
+use std::{
+    env,
+    error::Error,
+    fs,
+    path::{self, Component, Path, PathBuf},
+    sync::OnceLock,
+};
+
+static PROJECT_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// How many collection folders a saved request can be nested in.
+static MAX_COLLECTION_DEPTH: usize = 8;
+
+/// Build and dependency folders that are never searched for saved requests.
+static SKIPPED_FOLDERS: [&str; 3] = ["target", "node_modules", "vendor"];
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SavedRequestInfo {
@@ -16,167 +33,378 @@ pub struct SavedRequestInfo {
     pub request_type: String,
 }
 
-fn validate_prject() {
-    get_project().expect("Valid Gemon project not found!");
+/// Uses `path`, a project folder or its gemon.json, instead of looking for the project from the
+/// current folder up. Takes precedence over `GEMON_PROJECT`.
+pub fn set_project_override(path: PathBuf) {
+    let _ = PROJECT_OVERRIDE.set(path);
 }
 
-pub fn get_project() -> Option<Project> {
-    let project_str = match fs::read_to_string(PROJECT_ROOT_FILE) {
-        Ok(ps) => ps,
-        Err(err) => match err.kind() {
-            std::io::ErrorKind::NotFound => return None,
-            _ => panic!("Error reading project file"),
-        },
+/// Folder the project was pointed at with `--project` or `GEMON_PROJECT`.
+fn project_override() -> Option<PathBuf> {
+    let path = PROJECT_OVERRIDE.get().cloned().or_else(|| {
+        env::var_os(PROJECT_ENV)
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+    })?;
+    match path.file_name() {
+        Some(file) if file == PROJECT_ROOT_FILE && !path.is_dir() => {
+            path.parent().map(Path::to_path_buf)
+        }
+        _ => Some(path),
+    }
+}
+
+/// Folder `gemon init` creates the project in.
+pub fn new_project_root() -> PathBuf {
+    project_override().unwrap_or_default()
+}
+
+/// Folder of the project: the override, or else the closest folder with a gemon.json from the
+/// current one up, like git. It is relative to the current folder (e.g. `../..`) and empty for
+/// the current folder, so paths in messages read as before.
+pub fn project_root() -> Result<PathBuf, ProjectError> {
+    if let Some(root) = project_override() {
+        let file = root.join(PROJECT_ROOT_FILE);
+        return match file.is_file() {
+            true => Ok(root),
+            false => Err(ProjectError::NotFound(file.display().to_string())),
+        };
+    }
+
+    let current = env::current_dir().map_err(|err| ProjectError::io(".", err))?;
+    let mut root = PathBuf::new();
+    for dir in current.ancestors() {
+        if dir.join(PROJECT_ROOT_FILE).is_file() {
+            return Ok(root);
+        }
+        root.push("..");
+    }
+    Err(ProjectError::NotAProject)
+}
+
+/// `path` inside the project folder.
+pub fn project_path(path: impl AsRef<Path>) -> Result<PathBuf, ProjectError> {
+    Ok(project_root()?.join(path))
+}
+
+/// `path` as stored in gemon.json: relative to the project folder when it is inside it, absolute
+/// otherwise, so it points at the same file from any folder.
+pub fn stored_path(path: &str) -> Result<String, ProjectError> {
+    let root = project_root()?;
+    let root = match root.as_os_str().is_empty() {
+        true => Path::new("."),
+        false => root.as_path(),
     };
+    let root = fs::canonicalize(root).map_err(|err| ProjectError::io(root, err))?;
+    let absolute = fs::canonicalize(path)
+        .or_else(|_| path::absolute(path))
+        .map_err(|err| ProjectError::io(path, err))?;
+    let stored = match absolute.strip_prefix(&root) {
+        Ok(relative) => relative.to_path_buf(),
+        Err(_) => absolute.clone(),
+    };
+    Ok(stored.to_string_lossy().into_owned())
+}
+
+/// A file path of a saved request or setting as it is written to the project. Paths with
+/// environment placeholders are kept as they are, they are only known when the file is read.
+fn stored_file_path(path: &str) -> Result<String, ProjectError> {
+    match path.is_empty() || path.contains('{') {
+        true => Ok(path.to_string()),
+        false => stored_path(path),
+    }
+}
+
+/// A stored file path as it is read from the current folder, stored paths are relative to the
+/// project folder.
+fn resolved_file_path(path: &str) -> Result<String, ProjectError> {
+    match path.is_empty() {
+        true => Ok(String::new()),
+        false => Ok(project_path(path)?.to_string_lossy().into_owned()),
+    }
+}
+
+fn validate_project() -> Result<(), ProjectError> {
+    get_project().map(|_| ())
+}
 
-    let project: Project = serde_json::from_str(&project_str).expect("Error parsing project file");
-    Some(project)
+pub fn get_project() -> Result<Project, ProjectError> {
+    let path = project_path(PROJECT_ROOT_FILE)?;
+    let project_str = fs::read_to_string(&path).map_err(|err| ProjectError::io(&path, err))?;
+    serde_json::from_str(&project_str).map_err(|err| ProjectError::parse(&path, err))
 }
 
 pub fn create_project(name: &str) -> EmptyResult {
     Project::init_named(name)
 }
 
+/// Saved requests of the project, those in collection folders are named by their path, e.g.
+/// `users/create`. They are sorted by path, so the requests of a collection are listed together.
 pub fn list_saved_requests() -> Result<Vec<SavedRequestInfo>, Box<dyn Error>> {
-    get_project().ok_or(ProjectError {
-        message: String::from("Project not found!"),
-    })?;
+    validate_project()?;
+    let root = project_root()?;
+    let dir = match root.as_os_str().is_empty() {
+        true => Path::new("."),
+        false => root.as_path(),
+    };
 
     let mut requests = Vec::new();
-    for entry in fs::read_dir(".")? {
-        let entry = entry?;
+    collect_saved_requests(dir, None, &mut requests)?;
+    requests.sort_by(|left, right| left.name.split('/').cmp(right.name.split('/')));
+    Ok(requests)
+}
+
+/// Adds the requests saved in `dir` and in the collection folders below it. Hidden folders,
+/// build folders, the contents of request folders and folders deeper than
+/// `MAX_COLLECTION_DEPTH` are not searched, and unreadable collection folders are left out.
+fn collect_saved_requests(
+    dir: &Path,
+    collection: Option<&str>,
+    requests: &mut Vec<SavedRequestInfo>,
+) -> Result<(), ProjectError> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) if collection.is_some() => return Ok(()),
+        Err(err) => return Err(ProjectError::io(dir, err)),
+    };
+    let depth = collection.map_or(0, |collection| collection.split('/').count());
+    for entry in entries.flatten() {
         let path = entry.path();
-        if !path.is_dir() {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if !path.is_dir() || file_name.starts_with('.') {
             continue;
         }
+        if collection.is_none() && SKIPPED_FOLDERS.contains(&file_name.as_str()) {
+            continue;
+        }
+        let name = match collection {
+            Some(collection) => format!("{collection}/{file_name}"),
+            None => file_name,
+        };
 
         let marker_path = path.join(".marker");
-        let metadata_path = path.join("metadata.json");
-        let body_path = path.join("body.json");
-        if !(marker_path.exists() && metadata_path.exists() && body_path.exists()) {
+        if !marker_path.exists() {
+            // Symbolic links are not followed into, they could lead back up
+            if depth < MAX_COLLECTION_DEPTH
+                && !entry
+                    .file_type()
+                    .is_ok_and(|file_type| file_type.is_symlink())
+            {
+                collect_saved_requests(&path, Some(&name), requests)?;
+            }
+            continue;
+        }
+        if !(path.join("metadata.json").exists() && path.join("body.json").exists()) {
             continue;
         }
 
-        let request_type = fs::read_to_string(marker_path)?.trim().to_string();
-        requests.push(SavedRequestInfo {
-            name: entry.file_name().to_string_lossy().to_string(),
-            request_type,
-        });
+        let request_type = read_request_file(&marker_path)?.trim().to_string();
+        requests.push(SavedRequestInfo { name, request_type });
     }
+    Ok(())
+}
 
-    requests.sort_by(|left, right| left.name.cmp(&right.name));
-    Ok(requests)
+/// Folder of the saved request `name`, which is a path of collection folders ending with the
+/// request folder, e.g. `users/create`.
+fn request_dir(name: &str) -> Result<PathBuf, ProjectError> {
+    let path = Path::new(name);
+    let valid = path.components().next().is_some()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+    if !valid {
+        return Err(ProjectError::Invalid(format!(
+            "'{name}' is not a request name, use folder names separated by /, e.g. users/create"
+        )));
+    }
+    if path.components().count() > MAX_COLLECTION_DEPTH + 1 {
+        return Err(ProjectError::Invalid(format!(
+            "'{name}' is nested too deep, use at most {MAX_COLLECTION_DEPTH} collection folders"
+        )));
+    }
+    project_path(path)
 }
 
-pub fn read_saved_rest_request(name: &str) -> Result<GemonRestRequest, Box<dyn Error>> {
-    get_project().ok_or(ProjectError {
-        message: String::from("Project not found!"),
-    })?;
+/// Sets the saved body of a request. Text gets `resolve` applied, a body that is not UTF-8 is
+/// set as a binary body.
+fn set_saved_body(
+    request: &mut impl GemonRequest,
+    dir: &Path,
+    resolve: fn(String) -> String,
+) -> Result<(), ProjectError> {
+    match fs::read(dir.join("body.json")).map(String::from_utf8) {
+        Ok(Ok(body)) => request.set_body(Some(resolve(body))),
+        Ok(Err(err)) => request.set_binary_body(err.into_bytes())?,
+        Err(_) => request.set_body(None),
+    }
+    Ok(())
+}
+
+fn read_saved_request<T>(
+    name: &str,
+    expected_type: &str,
+    resolve: fn(String) -> String,
+) -> Result<T, Box<dyn Error>>
+where
+    T: GemonRequest + serde::de::DeserializeOwned,
+{
+    validate_project()?;
 
-    let request_type = fs::read_to_string(format!("{name}/.marker"))?;
-    if request_type.trim() != "REST" {
-        return Err(Box::new(ProjectError {
-            message: format!("Saved request '{name}' is not a REST request"),
-        }));
+    let dir = request_dir(name)?;
+    let request_type = read_request_marker(name, &dir)?;
+    if request_type != expected_type {
+        return Err(Box::new(ProjectError::Invalid(format!(
+            "Saved request '{name}' is not a {expected_type} request"
+        ))));
     }
 
-    let metadata_json = fs::read_to_string(format!("{name}/metadata.json"))?;
-    let body = fs::read_to_string(format!("{name}/body.json")).ok();
-    let mut request: GemonRestRequest = serde_json::from_str(&metadata_json)?;
-    request.set_body(body);
+    let metadata_path = dir.join("metadata.json");
+    let metadata_json = resolve(read_request_file(&metadata_path)?);
+    let mut request: T = serde_json::from_str(&metadata_json)
+        .map_err(|err| ProjectError::parse(&metadata_path, err))?;
+    set_saved_body(&mut request, &dir, resolve)?;
+    request.map_paths(&resolved_file_path)?;
     Ok(request)
 }
 
-pub fn save_request(request: Box<impl GemonRequest>, name: &String) -> Box<impl GemonRequest> {
-    validate_prject();
-    let json_metadata = request.json_metadata();
-    let json_body = request.json_body();
-    let request_type_marker = request.request_type();
-    if let Err(err) = fs::read_dir(name) {
-        match err.kind() {
-            std::io::ErrorKind::NotFound => fs::create_dir(name).expect("Create dir failed!"),
-            std::io::ErrorKind::PermissionDenied => {
-                panic!("User does not have permissions to write to dir!")
-            }
-            _ => panic!("Error while trying to create dir for the request!"),
-        };
+pub fn read_saved_rest_request(name: &str) -> Result<GemonRestRequest, Box<dyn Error>> {
+    read_saved_request(name, "REST", |text| text)
+}
+
+/// Reads a saved REST request with the selected environment values filled in.
+pub fn resolve_saved_rest_request(name: &str) -> Result<GemonRestRequest, Box<dyn Error>> {
+    read_saved_request(name, "REST", Effector::apply_env_to_string)
+}
+
+pub fn read_saved_websocket_request(name: &str) -> Result<GemonWebsocketRequest, Box<dyn Error>> {
+    read_saved_request(name, "WEBSOCKET", |text| text)
+}
+
+/// Reads a file of a saved request, a missing file means a missing request.
+fn read_request_file(path: &Path) -> Result<String, ProjectError> {
+    fs::read_to_string(path).map_err(|err| ProjectError::io(path, err))
+}
+
+fn read_request_marker(name: &str, dir: &Path) -> Result<String, ProjectError> {
+    let marker_path = dir.join(".marker");
+    match read_request_file(&marker_path) {
+        Ok(request_type) => match request_type.trim() {
+            request_type @ ("REST" | "WEBSOCKET" | "PROTO") => Ok(request_type.to_string()),
+            other => Err(ProjectError::Invalid(format!(
+                "Unknown request type '{other}' in {}",
+                marker_path.display()
+            ))),
+        },
+        Err(ProjectError::NotFound(_)) => {
+            Err(ProjectError::NotFound(format!("Saved request '{name}'")))
+        }
+        Err(err) => Err(err),
     }
-    fs::write(format!("{}/metadata.json", name), json_metadata)
-        .expect("Could not create metadata file!");
-    fs::write(format!("{}/body.json", name), json_body).expect("Could not create body file!");
-    fs::write(format!("{}/.marker", name), request_type_marker)
-        .expect("Failed to mark request dir");
-    request
-}
-
-pub fn get_request(name: &String) -> Box<impl GemonRequest> {
-    validate_prject();
-    let _ = fs::read_dir(name)
-        .unwrap_or_else(|_| panic!("Could not find saved request with name: {}", name));
-    let request_type =
-        fs::read_to_string(format!("{name}/.marker")).expect("Could not read dir marker");
-    let metadata_json = Effector::apply_env_to_string(
-        fs::read_to_string(format!("{name}/metadata.json"))
-            .expect("Could not read metadata json file for request"),
-    );
-    let body_json = fs::read_to_string(format!("{name}/body.json"))
-        .map(Effector::apply_env_to_string)
-        .ok();
-    let mut request = RequestBuilder::build_from_string(&metadata_json, &request_type);
-    request.set_body(body_json);
-    request
+}
+
+fn write_request_file(path: &Path, contents: impl AsRef<[u8]>) -> Result<(), ProjectError> {
+    fs::write(path, contents).map_err(|err| ProjectError::io(path, err))
+}
+
+/// Saves the request under `name`. The files it reads are stored relative to the project, the
+/// returned request reads them from the current folder again.
+pub fn save_request(
+    mut request: Box<impl GemonRequest>,
+    name: &String,
+) -> Result<Box<impl GemonRequest>, ProjectError> {
+    validate_project()?;
+    let dir = request_dir(name)?;
+    // Requests are kept in collection folders but not in other request folders
+    for collection in Path::new(name).ancestors().skip(1) {
+        if !collection.as_os_str().is_empty() && project_path(collection)?.join(".marker").exists()
+        {
+            return Err(ProjectError::Invalid(format!(
+                "'{}' is a saved request, it cannot hold other requests",
+                collection.display()
+            )));
+        }
+    }
+    if dir.is_dir() && !dir.join(".marker").exists() {
+        let mut requests = Vec::new();
+        collect_saved_requests(&dir, Some(name), &mut requests)?;
+        if !requests.is_empty() {
+            return Err(ProjectError::Invalid(format!(
+                "'{name}' is a collection of saved requests"
+            )));
+        }
+    }
+    fs::create_dir_all(&dir).map_err(|err| ProjectError::io(&dir, err))?;
+    request.map_paths(&stored_file_path)?;
+    write_request_file(&dir.join("metadata.json"), request.json_metadata())?;
+    write_request_file(&dir.join("body.json"), request.body_bytes())?;
+    write_request_file(&dir.join(".marker"), request.request_type())?;
+    request.map_paths(&resolved_file_path)?;
+    Ok(request)
+}
+
+pub fn get_request(name: &str) -> Result<Box<AnyGemonRequest>, ProjectError> {
+    validate_project()?;
+    let dir = request_dir(name)?;
+    let request_type = read_request_marker(name, &dir)?;
+    let metadata_path = dir.join("metadata.json");
+    let metadata_json = Effector::apply_env_to_string(read_request_file(&metadata_path)?);
+    let mut request = RequestBuilder::build_from_string(&metadata_json, &request_type)
+        .map_err(|err| ProjectError::parse(&metadata_path, err))?;
+    set_saved_body(request.as_mut(), &dir, Effector::apply_env_to_string)?;
+    request.map_paths(&resolved_file_path)?;
+    Ok(request)
 }
 
 pub fn delete_request(name: &String) -> EmptyResult {
-    validate_prject();
-    fs::remove_dir_all(name).map_err(|err| err.into())
+    validate_project()?;
+    let dir = request_dir(name)?;
+    // Only request folders are removed, never a collection or any other folder
+    if !dir.join(".marker").is_file() {
+        return Err(ProjectError::NotFound(format!("Saved request '{name}'")).into());
+    }
+    fs::remove_dir_all(&dir).map_err(|err| ProjectError::io(&dir, err))?;
+    // Collections left empty go with it
+    for collection in Path::new(name).ancestors().skip(1) {
+        if collection.as_os_str().is_empty() || fs::remove_dir(project_path(collection)?).is_err() {
+            break;
+        }
+    }
+    Ok(())
 }
 
 pub fn add_env_value(name: &String, env_value: (String, String)) -> EmptyResult {
-    let mut project = get_project().ok_or(ProjectError {
-        message: String::from("Project not found!"),
-    })?;
+    let mut project = get_project()?;
     project.add_env_value(name, env_value);
     project.save()
 }
 
 pub fn remove_env_value(env: &String, key: &str) -> EmptyResult {
-    let mut project = get_project().ok_or(ProjectError {
-        message: String::from("Project not found!"),
-    })?;
+    let mut project = get_project()?;
     project.remove_env_value(env, key);
     project.save()
 }
 
 pub fn remove_env(env: &String) -> EmptyResult {
-    let mut project = get_project().ok_or(ProjectError {
-        message: String::from("Project not found!"),
-    })?;
+    let mut project = get_project()?;
     project.remove_env(env);
     project.save()
 }
 
 pub fn set_selected_env(env: &String) -> EmptyResult {
-    let mut project = get_project().ok_or(ProjectError {
-        message: String::from("Project not found!"),
-    })?;
+    let mut project = get_project()?;
     project.set_selected_env(env)?;
     project.save()
 }
 
 pub fn get_selected_env() -> Option<Environment> {
-    get_project().and_then(|p| p.get_selected_env())
+    get_project().ok().and_then(|p| p.get_selected_env())
 }
 
 pub fn print_selected_env() -> EmptyResult {
-    let project = get_project().ok_or(ProjectError {
-        message: String::from("Project not found!"),
-    })?;
+    let project = get_project()?;
     let selected_env = project
         .get_selected_env()
-        .ok_or(ProjectError {
-            message: String::from("Selected env not set!"),
-        })?
+        .ok_or_else(|| ProjectError::from("Selected env not set!"))?
         .values();
     let result = serde_json::to_string_pretty(&selected_env)?;
     println!("{}", result);
@@ -184,38 +412,75 @@ pub fn print_selected_env() -> EmptyResult {
 }
 
 pub fn print_all_env() -> EmptyResult {
-    let project = get_project().ok_or(ProjectError {
-        message: String::from("Project not found!"),
-    })?;
+    let project = get_project()?;
     let result = serde_json::to_string_pretty(&project.environments)?;
     println!("{}", result);
     Ok(())
 }
 
 pub fn add_authorization(authorization: &String) -> EmptyResult {
-    let mut project = get_project().ok_or(ProjectError {
-        message: String::from("Project not found!"),
-    })?;
+    let mut project = get_project()?;
     project.set_authorization(authorization)?;
     project.save()
 }
 
 pub fn remove_authorization() -> EmptyResult {
-    let mut project = get_project().ok_or(ProjectError {
-        message: String::from("Project not found!"),
-    })?;
+    let mut project = get_project()?;
     project.remove_authorization()?;
     project.save()
 }
 
 pub fn authorization() -> Option<String> {
-    let project = get_project()
-        .ok_or(ProjectError {
-            message: String::from("Project not found!"),
-        })
-        .ok();
-    match project {
-        Some(p) => p.authorization().cloned(),
-        None => None,
+    get_project()
+        .ok()
+        .and_then(|project| project.authorization().cloned())
+}
+
+pub fn add_tls_setting(setting: &str, value: &str) -> EmptyResult {
+    let mut project = get_project()?;
+    let value = match setting.trim() {
+        "cert" | "key" | "cacert" => stored_file_path(value.trim())?,
+        _ => value.to_string(),
+    };
+    project.set_tls_setting(setting, &value)?;
+    project.save()
+}
+
+pub fn remove_tls() -> EmptyResult {
+    let mut project = get_project()?;
+    project.remove_tls()?;
+    project.save()
+}
+
+/// TLS settings of the selected environment with their files read from the current folder,
+/// empty when there is no project.
+pub fn tls_settings() -> TlsSettings {
+    let mut tls = get_project()
+        .ok()
+        .and_then(|project| project.tls().cloned())
+        .unwrap_or_default();
+    match tls.map_paths(&resolved_file_path) {
+        Ok(()) => tls,
+        Err(_) => TlsSettings::default(),
     }
 }
+
+pub fn add_proxy_setting(setting: &str, value: &str) -> EmptyResult {
+    let mut project = get_project()?;
+    project.set_proxy_setting(setting, value)?;
+    project.save()
+}
+
+pub fn remove_proxy() -> EmptyResult {
+    let mut project = get_project()?;
+    project.remove_proxy()?;
+    project.save()
+}
+
+/// Proxy of the selected environment, empty when there is no project.
+pub fn proxy_settings() -> ProxySettings {
+    get_project()
+        .ok()
+        .and_then(|project| project.proxy().cloned())
+        .unwrap_or_default()
+}
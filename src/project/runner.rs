@@ -0,0 +1,199 @@
+use super::{
+    assertions::check_request,
+    project_handler::{add_env_value, get_project, list_saved_requests, project_path},
+    ProjectError,
+};
+use crate::{exit::ExitError, request::request_builder::GemonResponse, EmptyResult};
+use colored::Colorize;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{error::Error, fs};
+
+pub static RUNS_DIR: &str = "runs";
+
+/// Stores a value from a step's response into the selected environment. Exactly one of `json`
+/// (a JSON pointer into the body) or `header` should be set.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Capture {
+    pub env: String,
+    #[serde(default)]
+    pub json: Option<String>,
+    #[serde(default)]
+    pub header: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct RunStep {
+    pub request: String,
+    #[serde(default)]
+    pub capture: Vec<Capture>,
+    #[serde(default)]
+    pub continue_on_failure: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct RunFile {
+    #[serde(default)]
+    pub continue_on_failure: bool,
+    pub steps: Vec<RunStep>,
+}
+
+impl RunFile {
+    pub fn read(name: &str) -> Result<RunFile, Box<dyn Error>> {
+        let path = project_path(format!("{RUNS_DIR}/{name}.json"))?;
+        let content = fs::read_to_string(&path).map_err(|err| {
+            ProjectError::from(&format!(
+                "Could not read run file {}: {err}",
+                path.display()
+            ))
+        })?;
+        Ok(serde_json::from_str(&content)?)
+    }
+}
+
+impl Capture {
+    fn extract(&self, response: &GemonResponse) -> Result<String, String> {
+        if let Some(header) = self.header.as_ref() {
+            return response
+                .headers()
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(header))
+                .map(|(_, value)| value.to_owned())
+                .ok_or_else(|| format!("header '{header}' not found for '{}'", self.env));
+        }
+
+        let pointer = self.json.as_deref().unwrap_or_default();
+        let body = serde_json::from_slice::<Value>(response.data())
+            .map_err(|_| format!("response body is not JSON, cannot capture '{}'", self.env))?;
+        match body.pointer(pointer) {
+            Some(Value::String(text)) => Ok(text.to_owned()),
+            Some(value) => Ok(value.to_string()),
+            None => Err(format!("'{pointer}' not found for '{}'", self.env)),
+        }
+    }
+}
+
+async fn run_step(step: &RunStep, env: &String) -> (u128, Option<u16>, Vec<String>) {
+    let check = check_request(&step.request).await;
+    let Some(response) = check.response else {
+        return (check.elapsed_ms, None, check.failures);
+    };
+
+    let mut failures = check.failures;
+    if !check.has_assertions && response.status() >= 400 {
+        failures.push(format!("status {}", response.status()));
+    }
+
+    for capture in &step.capture {
+        match capture.extract(&response) {
+            Ok(value) => {
+                if let Err(err) = add_env_value(env, (capture.env.to_owned(), value)) {
+                    failures.push(err.to_string());
+                }
+            }
+            Err(message) => failures.push(message),
+        }
+    }
+
+    (check.elapsed_ms, Some(response.status()), failures)
+}
+
+/// Executes the steps of `runs/{name}.json` in order. Captured values are written to the
+/// selected environment, so later steps pick them up through the usual `{key}` placeholders.
+pub async fn run_collection(name: &str) -> EmptyResult {
+    let run = RunFile::read(name)?;
+    let env = get_project()?
+        .selected_environment_name()
+        .map(String::from)
+        .ok_or_else(|| ProjectError::from("Select an environment before running a collection"))?;
+    let saved = list_saved_requests()?
+        .into_iter()
+        .map(|request| request.name)
+        .collect::<Vec<_>>();
+
+    let mut failed = 0;
+    for (index, step) in run.steps.iter().enumerate() {
+        let (elapsed_ms, status, failures) = if saved.contains(&step.request) {
+            run_step(step, &env).await
+        } else {
+            (0, None, vec![String::from("saved request not found")])
+        };
+
+        let label = format!(
+            "{}. {} {}",
+            index + 1,
+            step.request,
+            status.map(|status| status.to_string()).unwrap_or_default()
+        );
+        let timing = format!("({elapsed_ms} ms)");
+        if failures.is_empty() {
+            println!("{} {} {}", "OK".green().bold(), label, timing.dimmed());
+            continue;
+        }
+
+        failed += 1;
+        println!("{} {} {}", "FAIL".red().bold(), label, timing.dimmed());
+        for failure in &failures {
+            println!("     {}", failure.red());
+        }
+        if !step.continue_on_failure.unwrap_or(run.continue_on_failure) {
+            println!("{}", "Stopping run after failed step".red().bold());
+            break;
+        }
+    }
+
+    if failed == 0 {
+        println!("{}", format!("Run '{name}' completed").green().bold());
+        Ok(())
+    } else {
+        Err(Box::new(ExitError::Assertion(format!(
+            "Run '{name}' had {failed} failed step(s)"
+        ))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Capture;
+    use crate::request::request_builder::GemonResponse;
+    use bytes::Bytes;
+    use std::collections::HashMap;
+
+    #[test]
+    fn captures_read_json_pointers_and_headers() {
+        let mut headers = HashMap::new();
+        headers.insert(String::from("X-Session"), String::from("abc"));
+        let response = GemonResponse::new(
+            Bytes::from(r#"{"token": "t1", "user": {"id": 7}}"#),
+            200,
+            headers,
+        );
+
+        let token = Capture {
+            env: String::from("token"),
+            json: Some(String::from("/token")),
+            header: None,
+        };
+        let id = Capture {
+            env: String::from("user_id"),
+            json: Some(String::from("/user/id")),
+            header: None,
+        };
+        let session = Capture {
+            env: String::from("session"),
+            json: None,
+            header: Some(String::from("x-session")),
+        };
+
+        assert_eq!(token.extract(&response), Ok(String::from("t1")));
+        assert_eq!(id.extract(&response), Ok(String::from("7")));
+        assert_eq!(session.extract(&response), Ok(String::from("abc")));
+        assert!(Capture {
+            env: String::from("missing"),
+            json: Some(String::from("/missing")),
+            header: None,
+        }
+        .extract(&response)
+        .is_err());
+    }
+}
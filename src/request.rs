@@ -1,25 +1,77 @@
 use self::request_builder::RequestBuilder;
-use crate::{config::GemonConfig, printer::PrinterBuilder, EmptyResult};
-use request_builder::GemonRequest;
+use crate::{
+    config::{types::GemonBodyType, GemonConfig},
+    exit::ExitError,
+    printer::PrinterBuilder,
+    EmptyResult,
+};
+use colored::Colorize;
+use request_builder::{AnyGemonRequest, GemonRequest};
+use rest_request::GemonRestRequest;
+use std::time::Instant;
 
+pub mod curl;
+pub mod multipart;
+pub mod proto_request;
+pub mod proxy;
 pub mod request_builder;
 pub mod rest_request;
+pub mod tls;
+pub mod websocket_request;
 
 pub struct Request;
 
 impl Request {
+    /// Executes and prints the request. A response with a status the call should fail on is
+    /// still printed before the call fails.
     pub async fn call(request: Box<impl GemonRequest>, config: &GemonConfig) -> EmptyResult {
         // Execute the request
-        let response = request.execute().await?;
+        let started = Instant::now();
+        let mut response = request
+            .execute()
+            .await
+            .map_err(ExitError::from_request_error)?;
+        response.set_elapsed(started.elapsed());
         // Build printer
         let printer = PrinterBuilder::build(config);
         // Print response
-        printer.print(response.data()).map_err(|err| err.into())
+        printer.print(&response)?;
+        // Check status
+        match config.gemon_fail_statuses() {
+            Some(statuses) if statuses.contains(response.status()) => {
+                Err(Box::new(ExitError::HttpStatus(response.status())))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Files are only uploaded by multipart bodies, other body types send `@` values as text.
+    fn warn_about_text_files(request: &GemonRestRequest) {
+        if request.body_type() == GemonBodyType::Multipart {
+            return;
+        }
+        let mut keys = request
+            .form_data()
+            .iter()
+            .filter(|(_, value)| multipart::is_file(value))
+            .map(|(key, _)| key.as_str())
+            .collect::<Vec<_>>();
+        keys.sort();
+        for key in keys {
+            eprintln!(
+                "{} form data '{key}' is sent as text, files are only uploaded with {}",
+                "Warning:".yellow().bold(),
+                "--body-type multipart".bold()
+            );
+        }
     }
 
     pub async fn execute(config: &GemonConfig) -> EmptyResult {
         // Build the request
-        let request = RequestBuilder::build(config);
+        let request = RequestBuilder::build(config)?;
+        if let AnyGemonRequest::Rest(rest) = request.as_ref() {
+            Request::warn_about_text_files(rest);
+        }
         // Call request
         Request::call(request, config).await
     }
@@ -0,0 +1,376 @@
+use super::{
+    multipart,
+    rest_request::{GemonRestRequest, GemonRestRequestBuilder},
+};
+use crate::{
+    config::types::{GemonBodyType, GemonMethodType},
+    constants::AUTHORIZATION,
+    project::ProjectError,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::{collections::HashMap, error::Error};
+
+/// The result of parsing a curl command: the request and every option that was ignored.
+#[derive(Debug, Clone)]
+pub struct CurlImport {
+    pub request: GemonRestRequest,
+    pub ignored: Vec<String>,
+}
+
+fn has_header(headers: &HashMap<String, String>, name: &str) -> bool {
+    headers.keys().any(|key| key.eq_ignore_ascii_case(name))
+}
+
+/// Renders a curl command sending the same request gemon would, including the Content-Type
+/// derived from the body type. `authorization` is added unless the request already carries one.
+/// Binary bodies are left out since they can't be written on a command line. HEAD requests use
+/// `-I`, curl would wait for a body that never comes with `-X HEAD`.
+pub fn to_curl(request: &GemonRestRequest, authorization: Option<&str>) -> String {
+    let method = match request.method() {
+        GemonMethodType::Head => String::from("-I"),
+        method => format!("-X {method}"),
+    };
+    let mut args = vec![format!(
+        "curl {method} {}",
+        shell_words::quote(request.uri())
+    )];
+
+    let body = request.body().filter(|body| !body.is_empty());
+    let mut headers = request.headers().clone();
+    if let Some(content_type) = request.content_type() {
+        headers.insert(String::from("Content-Type"), content_type.to_string());
+    }
+    if let Some(authorization) = authorization {
+        if !has_header(&headers, AUTHORIZATION) {
+            headers.insert(String::from("Authorization"), authorization.to_string());
+        }
+    }
+
+    let mut headers = headers.into_iter().collect::<Vec<_>>();
+    headers.sort();
+    for (key, value) in headers {
+        args.push(format!(
+            "-H {}",
+            shell_words::quote(&format!("{key}: {value}"))
+        ));
+    }
+
+    let mut form_data = request.form_data().iter().collect::<Vec<_>>();
+    form_data.sort();
+    match (request.body_type(), body) {
+        (GemonBodyType::None, _) => {}
+        (GemonBodyType::Multipart, _) => {
+            for (key, value) in form_data {
+                let text = multipart::text_value(value);
+                let option = match multipart::is_file(value) || !text.starts_with(['@', '<']) {
+                    true => "-F",
+                    false => "--form-string",
+                };
+                args.push(format!(
+                    "{option} {}",
+                    shell_words::quote(&format!("{key}={text}"))
+                ));
+            }
+        }
+        (GemonBodyType::FormUrlencoded, None) => {
+            for (key, value) in form_data {
+                args.push(format!(
+                    "--data-urlencode {}",
+                    shell_words::quote(&format!("{key}={}", multipart::text_value(value)))
+                ));
+            }
+        }
+        (GemonBodyType::Binary, Some(body)) => {
+            args.push(format!("--data-binary {}", shell_words::quote(body)))
+        }
+        (_, Some(body)) => args.push(format!("--data-raw {}", shell_words::quote(body))),
+        (_, None) => {}
+    }
+
+    args.join(" \\\n  ")
+}
+
+fn method_from(name: &str) -> Result<GemonMethodType, Box<dyn Error>> {
+    name.parse()
+        .map_err(|err: String| ProjectError::from(&err).into())
+}
+
+/// Splits `--data=value` and `-XPOST` style options into the option and its value.
+fn split_option(token: &str) -> (String, Option<String>) {
+    if let Some((option, value)) = token
+        .strip_prefix("--")
+        .and_then(|rest| rest.split_once('='))
+    {
+        return (format!("--{option}"), Some(value.to_string()));
+    }
+    if token.len() > 2 && token.starts_with('-') && !token.starts_with("--") {
+        let (option, value) = token.split_at(2);
+        if matches!(option, "-X" | "-H" | "-d" | "-F" | "-u") {
+            return (option.to_string(), Some(value.to_string()));
+        }
+    }
+    (token.to_string(), None)
+}
+
+/// Options curl accepts with a value that gemon does not translate.
+const IGNORED_WITH_VALUE: [&str; 12] = [
+    "-o",
+    "--output",
+    "-A",
+    "--user-agent",
+    "-e",
+    "--referer",
+    "-m",
+    "--max-time",
+    "-b",
+    "--cookie",
+    "--connect-timeout",
+    "--retry",
+];
+
+/// Joins lines continued with a trailing backslash, as pasted from a terminal or dev tools.
+pub fn join_continued_lines(command: &str) -> String {
+    command.replace("\\\r\n", " ").replace("\\\n", " ")
+}
+
+/// Parses a curl command line such as one copied from browser dev tools. Supports the method,
+/// headers, `-d`/`--data-raw`, `-F`, `--data-urlencode` and basic auth through `-u`. The body
+/// type follows the Content-Type header, otherwise the form encoding curl itself would use.
+pub fn from_curl(command: &str) -> Result<CurlImport, Box<dyn Error>> {
+    let mut tokens = shell_words::split(&join_continued_lines(command))?.into_iter();
+
+    let mut method = None;
+    let mut url = None;
+    let mut headers = HashMap::new();
+    let mut data: Vec<String> = Vec::new();
+    let mut form_data = HashMap::new();
+    let mut multipart = false;
+    let mut get = false;
+    let mut ignored = Vec::new();
+
+    while let Some(token) = tokens.next() {
+        if url.is_none() && method.is_none() && token == "curl" {
+            continue;
+        }
+
+        let (option, inline_value) = split_option(&token);
+        let takes_value = matches!(
+            option.as_str(),
+            "-X" | "--request"
+                | "-H"
+                | "--header"
+                | "-d"
+                | "--data"
+                | "--data-raw"
+                | "--data-binary"
+                | "--data-ascii"
+                | "--data-urlencode"
+                | "-F"
+                | "--form"
+                | "--form-string"
+                | "-u"
+                | "--user"
+                | "--url"
+        ) || IGNORED_WITH_VALUE.contains(&option.as_str());
+        let value = match (takes_value, inline_value) {
+            (true, Some(value)) => value,
+            (true, None) => tokens
+                .next()
+                .ok_or_else(|| ProjectError::from(&format!("Missing value for {option}")))?,
+            (false, _) => String::new(),
+        };
+
+        match option.as_str() {
+            "-X" | "--request" => method = Some(method_from(&value)?),
+            "-H" | "--header" => {
+                let (key, header_value) = value
+                    .split_once(':')
+                    .ok_or_else(|| ProjectError::from(&format!("Invalid header '{value}'")))?;
+                headers.insert(key.trim().to_string(), header_value.trim().to_string());
+            }
+            "-d" | "--data" | "--data-raw" | "--data-binary" | "--data-ascii" => {
+                if value.starts_with('@') && option != "--data-raw" {
+                    ignored.push(format!("{option} {value} (file bodies are not imported)"));
+                } else {
+                    data.push(value);
+                }
+            }
+            "--data-urlencode" | "-F" | "--form" | "--form-string" => {
+                let (key, field_value) = value.split_once('=').unwrap_or((&value, ""));
+                let field_value = match option.as_str() {
+                    "-F" | "--form" if field_value.starts_with('<') => {
+                        ignored.push(format!("{option} {value} (file contents are not imported)"));
+                        continue;
+                    }
+                    "--data-urlencode" | "--form-string" if field_value.starts_with('@') => {
+                        format!("\\{field_value}")
+                    }
+                    _ => field_value.to_string(),
+                };
+                multipart |= option != "--data-urlencode";
+                form_data.insert(key.to_string(), field_value);
+            }
+            "-u" | "--user" => {
+                headers.insert(
+                    String::from("Authorization"),
+                    format!("Basic {}", STANDARD.encode(value)),
+                );
+            }
+            "--url" => url = Some(value),
+            "-G" | "--get" => get = true,
+            "-I" | "--head" => method = Some(GemonMethodType::Head),
+            _ if IGNORED_WITH_VALUE.contains(&option.as_str()) => {
+                ignored.push(format!("{option} {value}"))
+            }
+            _ if option.starts_with('-') => ignored.push(option),
+            _ if url.is_none() => url = Some(token),
+            _ => ignored.push(token),
+        }
+    }
+
+    let url = url.ok_or_else(|| ProjectError::from("The curl command has no URL"))?;
+    let (url, body) = match (get, data.is_empty()) {
+        (true, false) => {
+            let separator = if url.contains('?') { '&' } else { '?' };
+            (format!("{url}{separator}{}", data.join("&")), None)
+        }
+        (_, true) => (url, None),
+        (false, false) => (url, Some(data.join("&"))),
+    };
+    let method = method.unwrap_or(if body.is_some() || !form_data.is_empty() {
+        GemonMethodType::Post
+    } else {
+        GemonMethodType::Get
+    });
+
+    let content_type = headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("content-type"))
+        .and_then(|(_, value)| GemonBodyType::from_content_type(value));
+    let body_type = match content_type {
+        _ if multipart => GemonBodyType::Multipart,
+        Some(body_type) => body_type,
+        None if body.is_some() || !form_data.is_empty() => GemonBodyType::FormUrlencoded,
+        None => GemonBodyType::None,
+    };
+
+    Ok(CurlImport {
+        request: GemonRestRequestBuilder::new()
+            .set_gemon_method_type(method)
+            .set_url(url)
+            .set_headers(&headers)
+            .set_body(body)
+            .set_body_type(Some(body_type))
+            .set_form_data(&form_data)
+            .build(),
+        ignored,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_curl, to_curl};
+    use crate::{
+        config::types::{GemonBodyType, GemonMethodType},
+        request::rest_request::GemonRestRequestBuilder,
+    };
+    use std::collections::HashMap;
+
+    #[test]
+    fn parses_pasted_curl_commands() {
+        let import = from_curl(
+            "curl 'https://api.example.com/users?page=1' \\\n  -H 'Content-Type: application/json' \\\n  -H \"X-Trace: abc\" \\\n  --data-raw '{\"name\": \"Ann\"}' \\\n  -u admin:secret --compressed",
+        )
+        .unwrap();
+
+        let request = import.request;
+        assert_eq!(request.method(), GemonMethodType::Post);
+        assert_eq!(request.uri(), "https://api.example.com/users?page=1");
+        assert_eq!(request.headers().get("X-Trace").unwrap(), "abc");
+        assert_eq!(
+            request.headers().get("Authorization").unwrap(),
+            "Basic YWRtaW46c2VjcmV0"
+        );
+        assert_eq!(request.body(), Some("{\"name\": \"Ann\"}"));
+        assert_eq!(import.ignored, vec![String::from("--compressed")]);
+    }
+
+    #[test]
+    fn exported_commands_import_back_to_the_same_request() {
+        let mut headers = HashMap::new();
+        headers.insert(String::from("X-Note"), String::from("it's here"));
+        let mut form_data = HashMap::new();
+        form_data.insert(String::from("name"), String::from("Ann Lee"));
+        let request = GemonRestRequestBuilder::new()
+            .set_gemon_method_type(GemonMethodType::Put)
+            .set_url(String::from("http://localhost:8080/users/1"))
+            .set_headers(&headers)
+            .set_form_data(&form_data)
+            .build();
+
+        let command = to_curl(&request, Some("Bearer token"));
+        let imported = from_curl(&command).unwrap().request;
+
+        assert_eq!(imported.method(), GemonMethodType::Put);
+        assert_eq!(imported.uri(), request.uri());
+        assert_eq!(imported.headers().get("X-Note").unwrap(), "it's here");
+        assert_eq!(
+            imported.headers().get("Authorization").unwrap(),
+            "Bearer token"
+        );
+        assert_eq!(imported.form_data(), request.form_data());
+        assert_eq!(imported.body(), None);
+        assert_eq!(imported.body_type(), GemonBodyType::FormUrlencoded);
+    }
+
+    #[test]
+    fn body_types_follow_curl_encodings() {
+        let multipart = from_curl(
+            "curl -F name=Ann -F 'avatar=@me.png;type=image/png' --form-string 'handle=@ann' https://api.example.com/avatars",
+        )
+        .unwrap();
+        assert_eq!(multipart.request.body_type(), GemonBodyType::Multipart);
+        let form_data = multipart.request.form_data();
+        assert_eq!(form_data.get("avatar").unwrap(), "@me.png;type=image/png");
+        assert_eq!(form_data.get("handle").unwrap(), "\\@ann");
+        let exported = from_curl(&to_curl(&multipart.request, None))
+            .unwrap()
+            .request;
+        assert_eq!(exported.body_type(), GemonBodyType::Multipart);
+        assert_eq!(exported.form_data(), multipart.request.form_data());
+
+        let form = from_curl("curl -d a=1 https://api.example.com")
+            .unwrap()
+            .request;
+        assert_eq!(form.body_type(), GemonBodyType::FormUrlencoded);
+        assert!(to_curl(&form, None).contains("Content-Type: application/x-www-form-urlencoded"));
+
+        let xml = from_curl("curl -H 'Content-Type: text/xml' -d '<a/>' https://api.example.com")
+            .unwrap()
+            .request;
+        assert_eq!(xml.body_type(), GemonBodyType::Xml);
+        assert!(!to_curl(&xml, None).contains("Accept"));
+    }
+
+    #[test]
+    fn head_requests_use_the_head_option() {
+        let request = GemonRestRequestBuilder::new()
+            .set_gemon_method_type(GemonMethodType::Head)
+            .set_url(String::from("http://localhost:8080/health"))
+            .build();
+
+        let command = to_curl(&request, None);
+        assert_eq!(command, "curl -I http://localhost:8080/health");
+        assert_eq!(
+            from_curl(&command).unwrap().request.method(),
+            GemonMethodType::Head
+        );
+        assert_eq!(
+            from_curl("curl --head https://api.example.com")
+                .unwrap()
+                .request
+                .method(),
+            GemonMethodType::Head
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use reqwest::multipart::{Form, Part};
+use std::{collections::HashMap, error::Error, fs, path::Path};
+
+/// A form data value that uploads a file, written like curl's `-F`: `@path` followed by the
+/// optional `;filename=name` and `;type=content/type` attributes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilePart {
+    pub path: String,
+    /// File name sent to the server, the name of the file by default.
+    pub file_name: Option<String>,
+    /// Content type of the part, guessed from the file extension by default.
+    pub content_type: Option<String>,
+}
+
+impl FilePart {
+    /// Parses a form data value, `None` when it is a text field.
+    pub fn parse(value: &str) -> Option<FilePart> {
+        let mut attributes = value.strip_prefix('@')?.split(';');
+        let mut part = FilePart {
+            path: attributes.next().unwrap_or_default().trim().to_string(),
+            file_name: None,
+            content_type: None,
+        };
+        for attribute in attributes {
+            match attribute.split_once('=') {
+                Some((name, value)) if name.trim() == "filename" => {
+                    part.file_name = Some(value.trim().to_string())
+                }
+                Some((name, value)) if name.trim() == "type" => {
+                    part.content_type = Some(value.trim().to_string())
+                }
+                _ => {
+                    part.path.push(';');
+                    part.path.push_str(attribute);
+                }
+            }
+        }
+        Some(part)
+    }
+
+    /// The form data value referencing this file.
+    pub fn to_value(&self) -> String {
+        let mut value = format!("@{}", self.path);
+        if let Some(file_name) = &self.file_name {
+            value.push_str(&format!(";filename={file_name}"));
+        }
+        if let Some(content_type) = &self.content_type {
+            value.push_str(&format!(";type={content_type}"));
+        }
+        value
+    }
+
+    fn part(&self) -> Result<Part, Box<dyn Error>> {
+        let bytes =
+            fs::read(&self.path).map_err(|err| format!("Could not read {}: {err}", self.path))?;
+        let content_type = match &self.content_type {
+            Some(content_type) => content_type.to_string(),
+            None => mime_guess::from_path(&self.path)
+                .first_or_octet_stream()
+                .to_string(),
+        };
+        let file_name = self.file_name.clone().or_else(|| {
+            Path::new(&self.path)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+        });
+        let mut part = Part::bytes(bytes).mime_str(&content_type)?;
+        if let Some(file_name) = file_name {
+            part = part.file_name(file_name);
+        }
+        Ok(part)
+    }
+}
+
+pub fn is_file(value: &str) -> bool {
+    value.starts_with('@')
+}
+
+/// Whether any value uploads a file, used to pick multipart for new requests without a body type.
+pub fn has_files(form_data: &HashMap<String, String>) -> bool {
+    form_data.values().any(|value| is_file(value))
+}
+
+/// The text sent for a text field, a leading `\@` sends a literal `@`.
+pub fn text_value(value: &str) -> &str {
+    value
+        .strip_prefix('\\')
+        .filter(|value| value.starts_with('@'))
+        .unwrap_or(value)
+}
+
+/// Turns a text field into a file reference to the same path and back.
+pub fn toggle_file(value: &str) -> String {
+    match value.strip_prefix('@') {
+        Some(path) => path.to_string(),
+        None => format!("@{}", text_value(value)),
+    }
+}
+
+/// Text fields with their escapes removed, as sent url-encoded.
+pub fn text_fields(form_data: &HashMap<String, String>) -> HashMap<&str, &str> {
+    form_data
+        .iter()
+        .map(|(key, value)| (key.as_str(), text_value(value)))
+        .collect()
+}
+
+/// Builds the multipart form, reading every referenced file. Parts are sorted by name and a
+/// missing file fails the request.
+pub fn form(form_data: &HashMap<String, String>) -> Result<Form, Box<dyn Error>> {
+    let mut fields = form_data.iter().collect::<Vec<_>>();
+    fields.sort();
+    fields
+        .into_iter()
+        .try_fold(Form::new(), |form, (key, value)| {
+            match FilePart::parse(value) {
+                Some(file) => {
+                    let part = file.part().map_err(|err| {
+                        format!("Form data '{key}': {err} (text starting with @ is written \\@)")
+                    })?;
+                    Ok(form.part(key.to_string(), part))
+                }
+                None => Ok(form.text(key.to_string(), text_value(value).to_string())),
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{form, has_files, text_value, toggle_file, FilePart};
+    use std::collections::HashMap;
+
+    #[test]
+    fn file_references_use_curl_syntax() {
+        let part = FilePart::parse("@./avatars/me.png;type=image/png;filename=avatar.png").unwrap();
+
+        assert_eq!(part.path, "./avatars/me.png");
+        assert_eq!(part.file_name.as_deref(), Some("avatar.png"));
+        assert_eq!(part.content_type.as_deref(), Some("image/png"));
+        assert_eq!(
+            part.to_value(),
+            "@./avatars/me.png;filename=avatar.png;type=image/png"
+        );
+        assert_eq!(FilePart::parse("Ann"), None);
+        assert_eq!(text_value("\\@ann"), "@ann");
+        assert_eq!(toggle_file("me.png"), "@me.png");
+        assert_eq!(toggle_file("@me.png"), "me.png");
+
+        let mut form_data = HashMap::new();
+        form_data.insert(String::from("user"), String::from("\\@ann"));
+        assert!(!has_files(&form_data));
+        form_data.insert(String::from("avatar"), String::from("@missing.png"));
+        assert!(has_files(&form_data));
+        let err = form(&form_data).unwrap_err().to_string();
+        assert!(err.starts_with("Form data 'avatar': Could not read missing.png"));
+    }
+}
@@ -0,0 +1,437 @@
+use super::request_builder::{GemonRequest, GemonResponse, PathMap};
+use crate::project::ProjectError;
+use bytes::{Buf, BufMut, Bytes};
+use http::uri::PathAndQuery;
+use prost::Message;
+use prost_reflect::{DynamicMessage, MessageDescriptor, MethodDescriptor};
+use serde_derive::{Deserialize, Serialize};
+use serde_json::json;
+use std::{collections::HashMap, error::Error, path::Path, str::FromStr};
+use tonic::{
+    client::Grpc,
+    codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder},
+    metadata::{MetadataKey, MetadataValue},
+    transport::{Channel, ClientTlsConfig},
+    Code, Status,
+};
+
+pub struct GemonProtoRequestBuilder {
+    url: Option<String>,
+    proto_file: Option<String>,
+    import_paths: Vec<String>,
+    grpc_method: Option<String>,
+    headers: HashMap<String, String>,
+    body: Option<String>,
+}
+
+impl GemonProtoRequestBuilder {
+    pub fn new() -> GemonProtoRequestBuilder {
+        GemonProtoRequestBuilder {
+            url: None,
+            proto_file: None,
+            import_paths: Vec::new(),
+            grpc_method: None,
+            headers: HashMap::new(),
+            body: None,
+        }
+    }
+
+    pub fn set_url(self, url: String) -> GemonProtoRequestBuilder {
+        GemonProtoRequestBuilder {
+            url: Some(url),
+            ..self
+        }
+    }
+
+    pub fn set_proto_file(self, proto_file: Option<String>) -> GemonProtoRequestBuilder {
+        GemonProtoRequestBuilder { proto_file, ..self }
+    }
+
+    pub fn set_import_paths(self, import_paths: &[String]) -> GemonProtoRequestBuilder {
+        GemonProtoRequestBuilder {
+            import_paths: import_paths.to_vec(),
+            ..self
+        }
+    }
+
+    pub fn set_grpc_method(self, grpc_method: Option<String>) -> GemonProtoRequestBuilder {
+        GemonProtoRequestBuilder {
+            grpc_method,
+            ..self
+        }
+    }
+
+    pub fn set_headers(self, headers: &HashMap<String, String>) -> GemonProtoRequestBuilder {
+        GemonProtoRequestBuilder {
+            headers: headers.clone(),
+            ..self
+        }
+    }
+
+    pub fn set_body(self, body: Option<String>) -> GemonProtoRequestBuilder {
+        GemonProtoRequestBuilder { body, ..self }
+    }
+
+    /// Fails when the uri, the proto file or the gRPC method was not given.
+    pub fn build(&self) -> Result<GemonProtoRequest, ProjectError> {
+        let missing = |option: &str| ProjectError::Invalid(format!("PROTO requests need {option}"));
+        Ok(GemonProtoRequest {
+            // The config defaults to an empty url when none was passed
+            uri: self
+                .url
+                .clone()
+                .filter(|url| !url.trim().is_empty())
+                .ok_or_else(|| missing("a uri (-u)"))?,
+            proto_file: self
+                .proto_file
+                .clone()
+                .ok_or_else(|| missing("a proto file (--proto-file)"))?,
+            import_paths: self.import_paths.clone(),
+            grpc_method: self
+                .grpc_method
+                .clone()
+                .ok_or_else(|| missing("a gRPC method (--grpc-method package.Service/Method)"))?,
+            headers: self.headers.clone(),
+            body: self.body.clone(),
+        })
+    }
+
+    pub fn build_from_string(content: &str) -> serde_json::Result<GemonProtoRequest> {
+        serde_json::from_str(content)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GemonProtoRequest {
+    uri: String,
+    proto_file: String,
+    #[serde(default)]
+    import_paths: Vec<String>,
+    grpc_method: String,
+    headers: HashMap<String, String>,
+    body: Option<String>,
+}
+
+impl GemonProtoRequest {
+    /// Compiles the proto file and looks up the unary method named `package.Service/Method`.
+    fn method_descriptor(&self) -> Result<MethodDescriptor, Box<dyn Error>> {
+        let (service_name, method_name) = self.grpc_method.split_once('/').ok_or_else(|| {
+            ProjectError::from("gRPC method must look like package.Service/Method")
+        })?;
+
+        let proto_dir = Path::new(&self.proto_file)
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."))
+            .to_path_buf();
+        let mut includes = vec![proto_dir];
+        includes.extend(self.import_paths.iter().map(Into::into));
+
+        let mut compiler = protox::Compiler::new(includes)?;
+        compiler.open_file(&self.proto_file)?;
+        let pool = compiler.descriptor_pool();
+
+        let service = pool
+            .get_service_by_name(service_name)
+            .ok_or_else(|| ProjectError::from(&format!("Service '{service_name}' not found")))?;
+        let method = service
+            .methods()
+            .find(|method| method.name() == method_name)
+            .ok_or_else(|| {
+                ProjectError::from(&format!(
+                    "Method '{method_name}' not found in '{service_name}'"
+                ))
+            })?;
+
+        if method.is_client_streaming() || method.is_server_streaming() {
+            return Err(ProjectError::from("Only unary gRPC methods are supported"));
+        }
+        Ok(method)
+    }
+
+    /// The JSON body as the input message of `method`, an empty body is an empty message.
+    fn request_message(&self, method: &MethodDescriptor) -> Result<DynamicMessage, Box<dyn Error>> {
+        let body = self
+            .body
+            .as_deref()
+            .filter(|body| !body.trim().is_empty())
+            .unwrap_or("{}");
+        let mut deserializer = serde_json::Deserializer::from_str(body);
+        let message = DynamicMessage::deserialize(method.input(), &mut deserializer)?;
+        deserializer.end()?;
+        Ok(message)
+    }
+
+    async fn channel(&self) -> Result<Channel, Box<dyn Error>> {
+        let mut endpoint = Channel::from_shared(self.uri.clone())?;
+        if self.uri.starts_with("https://") {
+            endpoint = endpoint.tls_config(ClientTlsConfig::new().with_native_roots())?;
+        }
+        Ok(endpoint.connect().await?)
+    }
+}
+
+/// Maps gRPC status codes onto the closest HTTP status so the response can be treated like a
+/// REST one by printers and callers.
+fn http_status(code: Code) -> u16 {
+    match code {
+        Code::Ok => 200,
+        Code::InvalidArgument | Code::FailedPrecondition | Code::OutOfRange => 400,
+        Code::Unauthenticated => 401,
+        Code::PermissionDenied => 403,
+        Code::NotFound => 404,
+        Code::AlreadyExists | Code::Aborted => 409,
+        Code::ResourceExhausted => 429,
+        Code::Cancelled => 499,
+        Code::Unimplemented => 501,
+        Code::Unavailable => 503,
+        Code::DeadlineExceeded => 504,
+        Code::Unknown | Code::Internal | Code::DataLoss => 500,
+    }
+}
+
+fn metadata_to_headers(metadata: &tonic::metadata::MetadataMap) -> HashMap<String, String> {
+    metadata
+        .clone()
+        .into_headers()
+        .iter()
+        .map(|(key, value)| {
+            (
+                key.as_str().to_string(),
+                value.to_str().unwrap_or_default().to_string(),
+            )
+        })
+        .collect()
+}
+
+impl GemonRequest for GemonProtoRequest {
+    async fn execute(&self) -> Result<GemonResponse, Box<dyn Error>> {
+        let method = self.method_descriptor()?;
+        let message = self.request_message(&method)?;
+
+        let mut request = tonic::Request::new(message);
+        for (key, value) in &self.headers {
+            request.metadata_mut().insert(
+                MetadataKey::from_bytes(key.to_lowercase().as_bytes())?,
+                MetadataValue::from_str(value)?,
+            );
+        }
+
+        let path = PathAndQuery::from_str(&format!(
+            "/{}/{}",
+            method.parent_service().full_name(),
+            method.name()
+        ))?;
+        let mut grpc = Grpc::new(self.channel().await?);
+        grpc.ready().await?;
+
+        let codec = DynamicCodec {
+            output: method.output(),
+        };
+        match grpc.unary(request, path, codec).await {
+            Ok(response) => {
+                let mut headers = metadata_to_headers(response.metadata());
+                headers.insert(String::from("grpc-status"), String::from("0"));
+                let data = serde_json::to_vec(response.get_ref())?;
+                Ok(GemonResponse::new(Bytes::from(data), 200, headers))
+            }
+            Err(status) => {
+                let mut headers = metadata_to_headers(status.metadata());
+                headers.insert(
+                    String::from("grpc-status"),
+                    (status.code() as i32).to_string(),
+                );
+                let data = serde_json::to_vec(&json!({
+                    "code": format!("{:?}", status.code()),
+                    "message": status.message(),
+                }))?;
+                Ok(GemonResponse::new(
+                    Bytes::from(data),
+                    http_status(status.code()),
+                    headers,
+                ))
+            }
+        }
+    }
+
+    fn json_metadata(&self) -> String {
+        let mut request_to_copy = self.clone();
+        request_to_copy.body.take();
+        serde_json::to_string_pretty(&request_to_copy)
+            .expect("Could not parse GemonProtoRequest to json string")
+    }
+
+    fn json_body(&self) -> String {
+        self.body.clone().unwrap_or_default()
+    }
+
+    fn request_type(&self) -> String {
+        String::from("PROTO")
+    }
+
+    fn set_body(&mut self, body: Option<String>) {
+        self.body = body
+    }
+
+    fn map_paths(&mut self, map: &PathMap) -> Result<(), ProjectError> {
+        self.proto_file = map(&self.proto_file)?;
+        for import_path in self.import_paths.iter_mut() {
+            *import_path = map(import_path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Encodes and decodes `DynamicMessage`s for a method resolved at runtime from a .proto file.
+struct DynamicCodec {
+    output: MessageDescriptor,
+}
+
+impl Codec for DynamicCodec {
+    type Encode = DynamicMessage;
+    type Decode = DynamicMessage;
+    type Encoder = DynamicEncoder;
+    type Decoder = DynamicDecoder;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        DynamicEncoder
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        DynamicDecoder {
+            output: self.output.clone(),
+        }
+    }
+}
+
+struct DynamicEncoder;
+
+impl Encoder for DynamicEncoder {
+    type Item = DynamicMessage;
+    type Error = Status;
+
+    fn encode(&mut self, item: Self::Item, dst: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+        let mut buffer = Vec::with_capacity(item.encoded_len());
+        item.encode(&mut buffer)
+            .map_err(|err| Status::internal(err.to_string()))?;
+        dst.put_slice(&buffer);
+        Ok(())
+    }
+}
+
+struct DynamicDecoder {
+    output: MessageDescriptor,
+}
+
+impl Decoder for DynamicDecoder {
+    type Item = DynamicMessage;
+    type Error = Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+        let bytes = src.copy_to_bytes(src.remaining());
+        DynamicMessage::decode(self.output.clone(), bytes)
+            .map(Some)
+            .map_err(|err| Status::internal(err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{http_status, GemonProtoRequestBuilder};
+    use crate::request::request_builder::GemonRequest;
+    use prost::Message;
+    use prost_reflect::DynamicMessage;
+    use serde_json::json;
+    use std::{collections::HashMap, fs};
+    use tonic::Code;
+
+    const SHOP_PROTO: &str = r#"
+        syntax = "proto3";
+        package shop;
+
+        message GetUser {
+            int64 id = 1;
+            string name = 2;
+            repeated string tags = 3;
+            bool active = 4;
+        }
+
+        service Users {
+            rpc Get(GetUser) returns (GetUser);
+            rpc Watch(GetUser) returns (stream GetUser);
+        }
+    "#;
+
+    #[test]
+    fn json_bodies_round_trip_through_the_method_messages() {
+        let dir = std::env::temp_dir().join(format!("gemon-proto-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let proto_file = dir.join("shop.proto");
+        fs::write(&proto_file, SHOP_PROTO).unwrap();
+        let builder = GemonProtoRequestBuilder::new()
+            .set_url(String::from("http://localhost:50051"))
+            .set_proto_file(Some(proto_file.to_string_lossy().to_string()))
+            .set_headers(&HashMap::new())
+            .set_body(Some(String::from(
+                r#"{"id": 7, "name": "ann", "tags": ["a", "b"], "active": true}"#,
+            )));
+
+        let request = builder
+            .set_grpc_method(Some(String::from("shop.Users/Get")))
+            .build()
+            .unwrap();
+        let method = request.method_descriptor().unwrap();
+        let message = request.request_message(&method).unwrap();
+        let decoded =
+            DynamicMessage::decode(method.output(), message.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(
+            serde_json::to_value(&decoded).unwrap(),
+            json!({"id": "7", "name": "ann", "tags": ["a", "b"], "active": true})
+        );
+
+        let mut empty = request.clone();
+        empty.set_body(None);
+        assert_eq!(empty.request_message(&method).unwrap().encoded_len(), 0);
+        let mut invalid = request.clone();
+        invalid.set_body(Some(String::from(r#"{"id": "seven"}"#)));
+        assert!(invalid.request_message(&method).is_err());
+
+        let streaming = GemonProtoRequestBuilder::new()
+            .set_url(String::from("http://localhost:50051"))
+            .set_proto_file(Some(proto_file.to_string_lossy().to_string()))
+            .set_grpc_method(Some(String::from("shop.Users/Watch")))
+            .build()
+            .unwrap();
+        let err = streaming.method_descriptor().unwrap_err();
+        assert!(err.to_string().contains("Only unary gRPC methods"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn missing_options_and_grpc_statuses_map_to_errors() {
+        let err = GemonProtoRequestBuilder::new()
+            .set_url(String::from("http://localhost:50051"))
+            .set_grpc_method(Some(String::from("shop.Users/Get")))
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "PROTO requests need a proto file (--proto-file)"
+        );
+        let err = GemonProtoRequestBuilder::new()
+            .set_url(String::from(" "))
+            .set_proto_file(Some(String::from("shop.proto")))
+            .set_grpc_method(Some(String::from("shop.Users/Get")))
+            .build()
+            .unwrap_err();
+        assert_eq!(err.to_string(), "PROTO requests need a uri (-u)");
+
+        assert_eq!(http_status(Code::Ok), 200);
+        assert_eq!(http_status(Code::InvalidArgument), 400);
+        assert_eq!(http_status(Code::Unauthenticated), 401);
+        assert_eq!(http_status(Code::NotFound), 404);
+        assert_eq!(http_status(Code::Unavailable), 503);
+        assert_eq!(http_status(Code::DeadlineExceeded), 504);
+        assert_eq!(http_status(Code::Internal), 500);
+    }
+}
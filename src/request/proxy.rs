@@ -0,0 +1,84 @@
+use reqwest::{ClientBuilder, NoProxy, Proxy};
+use serde_derive::{Deserialize, Serialize};
+use std::error::Error;
+
+pub static PROXY_SETTINGS: [&str; 4] = ["url", "username", "password", "no-proxy"];
+
+/// Proxy used by REST requests. It is stored per environment in the project and can be replaced
+/// for a single call. Without a url the system proxy variables are used.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProxySettings {
+    /// `http://`, `https://`, `socks5://` or `socks5h://` url of the proxy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    /// Comma separated hosts, domains and IP ranges that are reached directly.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub no_proxy: Option<String>,
+}
+
+impl ProxySettings {
+    pub fn from_url(url: &str) -> ProxySettings {
+        ProxySettings {
+            url: Some(url.to_string()),
+            ..ProxySettings::default()
+        }
+    }
+
+    /// Sets one setting by the name used on the command line, see `PROXY_SETTINGS`.
+    pub fn set(&mut self, setting: &str, value: &str) -> Result<(), String> {
+        let value = Some(value.trim().to_string());
+        match setting.trim() {
+            "url" => self.url = value,
+            "username" => self.username = value,
+            "password" => self.password = value,
+            "no-proxy" => self.no_proxy = value,
+            other => {
+                return Err(format!(
+                    "Unknown proxy setting '{other}', expected one of: {}",
+                    PROXY_SETTINGS.join(", ")
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    pub fn configure(&self, builder: ClientBuilder) -> Result<ClientBuilder, Box<dyn Error>> {
+        let Some(url) = &self.url else {
+            return Ok(builder);
+        };
+        let mut proxy = Proxy::all(url)?;
+        if let Some(username) = &self.username {
+            proxy = proxy.basic_auth(username, self.password.as_deref().unwrap_or_default());
+        }
+        if let Some(no_proxy) = &self.no_proxy {
+            proxy = proxy.no_proxy(NoProxy::from_string(no_proxy));
+        }
+        Ok(builder.proxy(proxy))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ProxySettings;
+
+    #[test]
+    fn settings_are_set_by_their_command_line_names() {
+        let mut proxy = ProxySettings::default();
+        proxy.set("url", " http://proxy.corp:3128 ").unwrap();
+        proxy.set("username", "jane").unwrap();
+        proxy.set("no-proxy", "localhost,.internal").unwrap();
+
+        assert_eq!(proxy.url.as_deref(), Some("http://proxy.corp:3128"));
+        assert_eq!(proxy.username.as_deref(), Some("jane"));
+        assert_eq!(proxy.no_proxy.as_deref(), Some("localhost,.internal"));
+        assert!(proxy.set("port", "3128").is_err());
+        assert!(proxy.configure(reqwest::Client::builder()).is_ok());
+        assert!(ProxySettings::from_url("not a url")
+            .configure(reqwest::Client::builder())
+            .is_err());
+    }
+}
@@ -1,12 +1,21 @@
-use super::rest_request::{GemonRestRequest, GemonRestRequestBuilder};
+use super::{
+    multipart,
+    proto_request::{GemonProtoRequest, GemonProtoRequestBuilder},
+    rest_request::{GemonRestRequest, GemonRestRequestBuilder},
+    websocket_request::{GemonWebsocketRequest, GemonWebsocketRequestBuilder},
+};
 use crate::{
-    config::{types::GemonType, GemonConfig},
+    config::{
+        types::{GemonBodyType, GemonType},
+        GemonConfig,
+    },
     constants::AUTHORIZATION,
-    project::project_handler::authorization,
+    project::{project_handler::authorization, ProjectError},
 };
 use bytes::Bytes;
 use std::collections::HashMap;
 use std::error::Error;
+use std::time::Duration;
 
 pub trait GemonRequest {
     async fn execute(&self) -> Result<GemonResponse, Box<dyn Error>>;
@@ -14,12 +23,48 @@ pub trait GemonRequest {
     fn json_body(&self) -> String;
     fn request_type(&self) -> String;
     fn set_body(&mut self, body: Option<String>);
+
+    /// Bytes stored as the body of a saved request.
+    fn body_bytes(&self) -> Vec<u8> {
+        self.json_body().into_bytes()
+    }
+
+    /// Sets a body that is not UTF-8 text. Only REST requests send binary bodies, the others
+    /// fail instead of dropping it.
+    fn set_binary_body(&mut self, _body: Vec<u8>) -> Result<(), ProjectError> {
+        Err(binary_body_error(&self.request_type()))
+    }
+
+    /// Replaces every file path the request reads with `map(path)`.
+    fn map_paths(&mut self, _map: &PathMap) -> Result<(), ProjectError> {
+        Ok(())
+    }
+}
+
+/// Rewrites a file path, e.g. between its stored and its resolved form.
+pub type PathMap = dyn Fn(&str) -> Result<String, ProjectError>;
+
+fn binary_body_error(request_type: &str) -> ProjectError {
+    ProjectError::Invalid(format!(
+        "Binary bodies are only supported for REST, the body of a {request_type} request must be UTF-8 text"
+    ))
+}
+
+/// The request line and headers of a request as it was sent.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SentRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
 }
 
 pub struct GemonResponse {
     data: Bytes,
     status: u16,
     headers: HashMap<String, String>,
+    version: Option<String>,
+    elapsed: Option<Duration>,
+    request: Option<SentRequest>,
 }
 
 impl GemonResponse {
@@ -28,9 +73,31 @@ impl GemonResponse {
             data,
             status,
             headers,
+            version: None,
+            elapsed: None,
+            request: None,
+        }
+    }
+
+    /// Sets the protocol version of the status line, e.g. `HTTP/1.1`.
+    pub fn with_version(self, version: String) -> GemonResponse {
+        GemonResponse {
+            version: Some(version),
+            ..self
+        }
+    }
+
+    pub fn with_request(self, request: SentRequest) -> GemonResponse {
+        GemonResponse {
+            request: Some(request),
+            ..self
         }
     }
 
+    pub fn set_elapsed(&mut self, elapsed: Duration) {
+        self.elapsed = Some(elapsed);
+    }
+
     pub fn data(&self) -> &Bytes {
         &self.data
     }
@@ -42,47 +109,211 @@ impl GemonResponse {
     pub fn headers(&self) -> &HashMap<String, String> {
         &self.headers
     }
+
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /// Time from sending the request until the whole response was read, retries included.
+    pub fn elapsed(&self) -> Option<Duration> {
+        self.elapsed
+    }
+
+    /// The request as it was sent, only known for REST requests.
+    pub fn request(&self) -> Option<&SentRequest> {
+        self.request.as_ref()
+    }
+}
+
+/// Any request gemon knows how to execute, so builders can return a single concrete type
+/// regardless of the request type marker.
+pub enum AnyGemonRequest {
+    Rest(GemonRestRequest),
+    Websocket(GemonWebsocketRequest),
+    Proto(GemonProtoRequest),
+}
+
+impl AnyGemonRequest {
+    /// Applies the transport options, TLS settings and proxy passed for a single call of a
+    /// saved REST request on top of the saved ones. A body passed for the call replaces the
+    /// saved body of any request.
+    pub fn apply_overrides(&mut self, config: &GemonConfig) -> Result<(), ProjectError> {
+        if let AnyGemonRequest::Rest(request) = self {
+            let options = request.options().merged(config.gemon_request_options());
+            request.set_options(options);
+            request.set_tls(config.gemon_tls().clone());
+            request.set_proxy(config.gemon_proxy());
+            if let Some(body_type) = config.gemon_body_type() {
+                request.set_body_type(body_type);
+            }
+        }
+        if let Some(body) = config.gemon_binary_body() {
+            self.set_binary_body(body.to_vec())?;
+        } else if config.gemon_body().is_some() {
+            self.set_body(config.gemon_body());
+        }
+        Ok(())
+    }
+}
+
+impl GemonRequest for AnyGemonRequest {
+    async fn execute(&self) -> Result<GemonResponse, Box<dyn Error>> {
+        match self {
+            AnyGemonRequest::Rest(request) => request.execute().await,
+            AnyGemonRequest::Websocket(request) => request.execute().await,
+            AnyGemonRequest::Proto(request) => request.execute().await,
+        }
+    }
+
+    fn json_metadata(&self) -> String {
+        match self {
+            AnyGemonRequest::Rest(request) => request.json_metadata(),
+            AnyGemonRequest::Websocket(request) => request.json_metadata(),
+            AnyGemonRequest::Proto(request) => request.json_metadata(),
+        }
+    }
+
+    fn json_body(&self) -> String {
+        match self {
+            AnyGemonRequest::Rest(request) => request.json_body(),
+            AnyGemonRequest::Websocket(request) => request.json_body(),
+            AnyGemonRequest::Proto(request) => request.json_body(),
+        }
+    }
+
+    fn request_type(&self) -> String {
+        match self {
+            AnyGemonRequest::Rest(request) => request.request_type(),
+            AnyGemonRequest::Websocket(request) => request.request_type(),
+            AnyGemonRequest::Proto(request) => request.request_type(),
+        }
+    }
+
+    fn set_body(&mut self, body: Option<String>) {
+        match self {
+            AnyGemonRequest::Rest(request) => request.set_body(body),
+            AnyGemonRequest::Websocket(request) => request.set_body(body),
+            AnyGemonRequest::Proto(request) => request.set_body(body),
+        }
+    }
+
+    fn body_bytes(&self) -> Vec<u8> {
+        match self {
+            AnyGemonRequest::Rest(request) => request.body_bytes(),
+            AnyGemonRequest::Websocket(request) => request.body_bytes(),
+            AnyGemonRequest::Proto(request) => request.body_bytes(),
+        }
+    }
+
+    fn set_binary_body(&mut self, body: Vec<u8>) -> Result<(), ProjectError> {
+        match self {
+            AnyGemonRequest::Rest(request) => request.set_binary_body(body),
+            AnyGemonRequest::Websocket(request) => request.set_binary_body(body),
+            AnyGemonRequest::Proto(request) => request.set_binary_body(body),
+        }
+    }
+
+    fn map_paths(&mut self, map: &PathMap) -> Result<(), ProjectError> {
+        match self {
+            AnyGemonRequest::Rest(request) => request.map_paths(map),
+            AnyGemonRequest::Websocket(request) => request.map_paths(map),
+            AnyGemonRequest::Proto(request) => request.map_paths(map),
+        }
+    }
 }
 
 pub struct RequestBuilder;
 
 impl RequestBuilder {
-    fn build_rest_request(config: &GemonConfig) -> Box<GemonRestRequest> {
+    fn headers_with_authorization(config: &GemonConfig) -> HashMap<String, String> {
         let mut headers = config.gemon_headers().clone();
         if config.gemon_secure() && !headers.contains_key(AUTHORIZATION) {
             if let Some(authorization) = authorization() {
                 headers.insert(AUTHORIZATION.to_string(), authorization.to_string());
             }
         }
-        Box::new(
-            GemonRestRequestBuilder::new()
-                .set_gemon_method_type(config.gemon_method_type())
-                .set_url(config.gemon_url())
-                .set_headers(&headers)
-                .set_body(config.gemon_body())
-                .set_form_data(config.gemon_form_data())
-                .build(),
-        )
-    }
-
-    fn build_rest_request_from_string(content: &str) -> Box<GemonRestRequest> {
-        Box::new(GemonRestRequestBuilder::build_from_string(content))
-    }
-
-    pub fn build_from_string(content: &str, request_type: &str) -> Box<impl GemonRequest> {
-        match request_type {
-            "REST" => RequestBuilder::build_rest_request_from_string(content),
-            "WEBSOCKET" => todo!(),
-            "PROTO" => todo!(),
-            _ => panic!("Invalid request_type marker"),
-        }
+        headers
     }
 
-    pub fn build(config: &GemonConfig) -> Box<impl GemonRequest> {
-        match config.gemon_type() {
-            GemonType::Rest => RequestBuilder::build_rest_request(config),
-            GemonType::Websocket => todo!(),
-            GemonType::Proto => todo!(),
+    /// The body type passed, or multipart when form data of a new request uploads a file. Saved
+    /// requests never infer multipart, so their `@` values stay text.
+    fn body_type(config: &GemonConfig) -> Option<GemonBodyType> {
+        config.gemon_body_type().or_else(|| {
+            multipart::has_files(config.gemon_form_data()).then_some(GemonBodyType::Multipart)
+        })
+    }
+
+    fn build_rest_request(config: &GemonConfig) -> GemonRestRequest {
+        GemonRestRequestBuilder::new()
+            .set_gemon_method_type(config.gemon_method_type())
+            .set_url(config.gemon_url())
+            .set_headers(&RequestBuilder::headers_with_authorization(config))
+            .set_body(config.gemon_body())
+            .set_binary_body(config.gemon_binary_body())
+            .set_body_type(Self::body_type(config))
+            .set_form_data(config.gemon_form_data())
+            .set_options(config.gemon_request_options())
+            .set_tls(config.gemon_tls())
+            .set_proxy(config.gemon_proxy())
+            .build()
+    }
+
+    fn build_websocket_request(config: &GemonConfig) -> GemonWebsocketRequest {
+        GemonWebsocketRequestBuilder::new()
+            .set_url(config.gemon_url())
+            .set_headers(&RequestBuilder::headers_with_authorization(config))
+            .set_body(config.gemon_body())
+            .set_frames_file(config.gemon_frames_file())
+            .set_timeout_ms(config.gemon_websocket_timeout_ms())
+            .set_max_messages(config.gemon_websocket_max_messages())
+            .build()
+    }
+
+    fn build_proto_request(config: &GemonConfig) -> Result<GemonProtoRequest, ProjectError> {
+        GemonProtoRequestBuilder::new()
+            .set_url(config.gemon_url())
+            .set_proto_file(config.gemon_proto_file())
+            .set_import_paths(config.gemon_proto_import_paths())
+            .set_grpc_method(config.gemon_grpc_method())
+            .set_headers(&RequestBuilder::headers_with_authorization(config))
+            .set_body(config.gemon_body())
+            .build()
+    }
+
+    pub fn build_from_string(
+        content: &str,
+        request_type: &str,
+    ) -> serde_json::Result<Box<AnyGemonRequest>> {
+        Ok(Box::new(match request_type.trim() {
+            "REST" => AnyGemonRequest::Rest(GemonRestRequestBuilder::build_from_string(content)?),
+            "WEBSOCKET" => AnyGemonRequest::Websocket(
+                GemonWebsocketRequestBuilder::build_from_string(content)?,
+            ),
+            "PROTO" => {
+                AnyGemonRequest::Proto(GemonProtoRequestBuilder::build_from_string(content)?)
+            }
+            other => {
+                return Err(serde::de::Error::custom(format!(
+                    "unknown request type '{other}' in .marker"
+                )))
+            }
+        }))
+    }
+
+    /// Builds the request described by `config`, failing when a required option is missing or
+    /// a binary body is given for a request that is not REST.
+    pub fn build(config: &GemonConfig) -> Result<Box<AnyGemonRequest>, ProjectError> {
+        if *config.gemon_type() != GemonType::Rest && config.gemon_binary_body().is_some() {
+            return Err(binary_body_error(config.gemon_type().as_str()));
         }
+        Ok(Box::new(match config.gemon_type() {
+            GemonType::Rest => AnyGemonRequest::Rest(RequestBuilder::build_rest_request(config)),
+            GemonType::Websocket => {
+                AnyGemonRequest::Websocket(RequestBuilder::build_websocket_request(config))
+            }
+            GemonType::Proto => {
+                AnyGemonRequest::Proto(RequestBuilder::build_proto_request(config)?)
+            }
+        }))
     }
 }
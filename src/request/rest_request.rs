@@ -1,12 +1,89 @@
-use super::request_builder::{GemonRequest, GemonResponse};
-use crate::config::types::GemonMethodType;
-use crate::constants;
+use super::request_builder::{GemonRequest, GemonResponse, PathMap, SentRequest};
+use super::{multipart, proxy::ProxySettings, tls::TlsSettings};
+use crate::config::types::{GemonBodyType, GemonMethodType};
+use crate::project::{
+    cookies::{load_cookie_jar, save_cookie_jar},
+    project_handler::{proxy_settings, tls_settings},
+    ProjectError,
+};
 use reqwest::{
     self,
-    header::{self, HeaderMap, ACCEPT, CONTENT_TYPE},
+    cookie::CookieStore,
+    header::{self, HeaderMap, HeaderValue, ACCEPT, CONTENT_LENGTH, CONTENT_TYPE, COOKIE, HOST},
 };
+use reqwest_cookie_store::CookieStoreMutex;
 use serde_derive::{Deserialize, Serialize};
-use std::{collections::HashMap, error::Error};
+use std::{collections::HashMap, error::Error, sync::Arc, time::Duration};
+
+pub static DEFAULT_RETRY_BACKOFF_MS: u64 = 500;
+
+/// Transport settings of a REST request. Unset values fall back to reqwest's defaults: no
+/// timeouts, up to 10 redirects and no retries.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RequestOptions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connect_timeout_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// `Some(0)` disables following redirects.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_redirects: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+    /// Delay before the first retry, doubled for every following one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_backoff_ms: Option<u64>,
+    /// Response statuses that are retried besides connection errors and timeouts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub retry_statuses: Vec<u16>,
+}
+
+impl RequestOptions {
+    /// Returns these options with every value that is set in `overrides` replaced.
+    pub fn merged(&self, overrides: &RequestOptions) -> RequestOptions {
+        RequestOptions {
+            connect_timeout_ms: overrides.connect_timeout_ms.or(self.connect_timeout_ms),
+            timeout_ms: overrides.timeout_ms.or(self.timeout_ms),
+            max_redirects: overrides.max_redirects.or(self.max_redirects),
+            retries: overrides.retries.or(self.retries),
+            retry_backoff_ms: overrides.retry_backoff_ms.or(self.retry_backoff_ms),
+            retry_statuses: if overrides.retry_statuses.is_empty() {
+                self.retry_statuses.clone()
+            } else {
+                overrides.retry_statuses.clone()
+            },
+        }
+    }
+
+    fn client(
+        &self,
+        tls: &TlsSettings,
+        proxy: &ProxySettings,
+        cookies: Arc<CookieStoreMutex>,
+    ) -> Result<reqwest::Client, Box<dyn Error>> {
+        let redirect = match self.max_redirects {
+            Some(0) => reqwest::redirect::Policy::none(),
+            Some(max) => reqwest::redirect::Policy::limited(max),
+            None => reqwest::redirect::Policy::default(),
+        };
+        let mut builder = reqwest::Client::builder()
+            .redirect(redirect)
+            .cookie_provider(cookies);
+        if let Some(ms) = self.connect_timeout_ms {
+            builder = builder.connect_timeout(Duration::from_millis(ms));
+        }
+        if let Some(ms) = self.timeout_ms {
+            builder = builder.timeout(Duration::from_millis(ms));
+        }
+        builder = proxy.configure(tls.configure(builder)?)?;
+        Ok(builder.build()?)
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        let base = self.retry_backoff_ms.unwrap_or(DEFAULT_RETRY_BACKOFF_MS);
+        Duration::from_millis(base.saturating_mul(2u64.saturating_pow(attempt)))
+    }
+}
 
 trait HeaderMapConverter {
     fn to_header_map(self) -> Result<HeaderMap, Box<dyn Error>>;
@@ -30,7 +107,12 @@ pub struct GemonRestRequestBuilder {
     url: Option<String>,
     headers: HashMap<String, String>,
     body: Option<String>,
+    binary_body: Option<Vec<u8>>,
+    body_type: Option<GemonBodyType>,
     form_data: HashMap<String, String>,
+    options: RequestOptions,
+    tls: TlsSettings,
+    proxy: Option<String>,
 }
 
 impl GemonRestRequestBuilder {
@@ -40,7 +122,12 @@ impl GemonRestRequestBuilder {
             url: None,
             headers: HashMap::new(),
             body: None,
+            binary_body: None,
+            body_type: None,
             form_data: HashMap::new(),
+            options: RequestOptions::default(),
+            tls: TlsSettings::default(),
+            proxy: None,
         }
     }
 
@@ -72,6 +159,18 @@ impl GemonRestRequestBuilder {
         GemonRestRequestBuilder { body, ..self }
     }
 
+    pub fn set_binary_body(self, binary_body: Option<&[u8]>) -> GemonRestRequestBuilder {
+        GemonRestRequestBuilder {
+            binary_body: binary_body.map(<[u8]>::to_vec),
+            ..self
+        }
+    }
+
+    /// Without a body type one is inferred from the body and form data when building.
+    pub fn set_body_type(self, body_type: Option<GemonBodyType>) -> GemonRestRequestBuilder {
+        GemonRestRequestBuilder { body_type, ..self }
+    }
+
     pub fn set_form_data(self, form_data: &HashMap<String, String>) -> GemonRestRequestBuilder {
         GemonRestRequestBuilder {
             form_data: form_data.clone(),
@@ -79,10 +178,29 @@ impl GemonRestRequestBuilder {
         }
     }
 
+    pub fn set_options(self, options: &RequestOptions) -> GemonRestRequestBuilder {
+        GemonRestRequestBuilder {
+            options: options.clone(),
+            ..self
+        }
+    }
+
+    pub fn set_tls(self, tls: &TlsSettings) -> GemonRestRequestBuilder {
+        GemonRestRequestBuilder {
+            tls: tls.clone(),
+            ..self
+        }
+    }
+
+    pub fn set_proxy(self, proxy: Option<String>) -> GemonRestRequestBuilder {
+        GemonRestRequestBuilder { proxy, ..self }
+    }
+
     pub fn build(&self) -> GemonRestRequest {
         GemonRestRequest {
             gemon_method_type: self
                 .gemon_method_type
+                .clone()
                 .expect("Method Type missing when building Rest request!"),
             uri: String::from(
                 self.url
@@ -91,12 +209,20 @@ impl GemonRestRequestBuilder {
             ),
             headers: self.headers.clone(),
             body: self.body.clone(),
+            binary_body: self.binary_body.clone(),
+            body_type: Some(
+                self.body_type
+                    .unwrap_or_else(|| GemonBodyType::infer(self.body.as_deref(), &self.form_data)),
+            ),
             form_data: self.form_data.clone(),
+            options: self.options.clone(),
+            tls: Box::new(self.tls.clone()),
+            proxy: self.proxy.clone(),
         }
     }
 
-    pub fn build_from_string(content: &str) -> GemonRestRequest {
-        serde_json::from_str(content).expect("Could not parse Gemon Rest Request")
+    pub fn build_from_string(content: &str) -> serde_json::Result<GemonRestRequest> {
+        serde_json::from_str(content)
     }
 }
 
@@ -106,12 +232,27 @@ pub struct GemonRestRequest {
     uri: String,
     headers: HashMap<String, String>,
     body: Option<String>,
+    /// Body that is not UTF-8 text, sent instead of `body`. It is saved as the raw body file.
+    #[serde(skip)]
+    binary_body: Option<Vec<u8>>,
+    /// Missing in requests saved before body types existed, see `body_type()`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body_type: Option<GemonBodyType>,
     form_data: HashMap<String, String>,
+    #[serde(default)]
+    options: RequestOptions,
+    /// TLS settings passed for this call, applied over the ones of the selected environment.
+    /// They are never saved with the request.
+    #[serde(skip)]
+    tls: Box<TlsSettings>,
+    /// Proxy url passed for this call, used instead of the proxy of the selected environment.
+    #[serde(skip)]
+    proxy: Option<String>,
 }
 
 impl GemonRestRequest {
     pub fn method(&self) -> GemonMethodType {
-        self.gemon_method_type
+        self.gemon_method_type.clone()
     }
 
     pub fn uri(&self) -> &str {
@@ -126,37 +267,188 @@ impl GemonRestRequest {
         self.body.as_deref()
     }
 
+    pub fn binary_body(&self) -> Option<&[u8]> {
+        self.binary_body.as_deref()
+    }
+
+    /// The bytes sent as the body, `None` for a missing or empty body.
+    fn body_content(&self) -> Option<Vec<u8>> {
+        match &self.binary_body {
+            Some(bytes) => Some(bytes.clone()),
+            None => self
+                .body()
+                .filter(|body| !body.is_empty())
+                .map(|body| body.as_bytes().to_vec()),
+        }
+    }
+
+    pub fn body_type(&self) -> GemonBodyType {
+        self.body_type
+            .unwrap_or_else(|| GemonBodyType::infer(self.body.as_deref(), &self.form_data))
+    }
+
+    pub fn set_body_type(&mut self, body_type: GemonBodyType) {
+        self.body_type = Some(body_type);
+    }
+
     pub fn form_data(&self) -> &HashMap<String, String> {
         &self.form_data
     }
-}
 
-impl GemonRequest for GemonRestRequest {
-    async fn execute(&self) -> Result<GemonResponse, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
-        let mut request = match self.gemon_method_type {
-            GemonMethodType::Get => client.get(&self.uri),
-            GemonMethodType::Post => client.post(&self.uri),
-            GemonMethodType::Delete => client.delete(&self.uri),
-            GemonMethodType::Put => client.put(&self.uri),
-            GemonMethodType::Patch => client.patch(&self.uri),
+    /// Content-Type derived from the body type, `None` when the request sets the header
+    /// itself or sends no body.
+    pub fn content_type(&self) -> Option<&'static str> {
+        let sets_content_type = self
+            .headers
+            .keys()
+            .any(|key| key.eq_ignore_ascii_case(CONTENT_TYPE.as_str()));
+        let sends_body = match self.body_type() {
+            GemonBodyType::FormUrlencoded => {
+                !self.form_data.is_empty() || self.body_content().is_some()
+            }
+            _ => self.body_content().is_some(),
         };
+        match sets_content_type || !sends_body {
+            true => None,
+            false => self.body_type().content_type(),
+        }
+    }
 
-        request = request
-            .header(CONTENT_TYPE, constants::DEFAULT_CONTENT_TYPE)
-            .header(ACCEPT, constants::DEFAULT_ACCEPT)
-            .headers(self.headers.clone().to_header_map()?);
+    pub fn options(&self) -> &RequestOptions {
+        &self.options
+    }
 
-        if !self.form_data.is_empty() {
-            request = request.form(&self.form_data);
+    pub fn set_options(&mut self, options: RequestOptions) {
+        self.options = options;
+    }
+
+    pub fn set_tls(&mut self, tls: TlsSettings) {
+        *self.tls = tls;
+    }
+
+    pub fn set_proxy(&mut self, proxy: Option<String>) {
+        self.proxy = proxy;
+    }
+
+    /// Encodes the body as its body type says. Form data is only sent by the form and
+    /// multipart types, a body is ignored by the multipart and none types.
+    fn request(&self, client: &reqwest::Client) -> Result<reqwest::RequestBuilder, Box<dyn Error>> {
+        let method = reqwest::Method::from_bytes(self.gemon_method_type.as_str().as_bytes())?;
+        let mut request = client.request(method, &self.uri);
+        if let Some(content_type) = self.content_type() {
+            request = request.header(CONTENT_TYPE, content_type);
         }
 
-        if let Some(body) = self.body.as_ref() {
-            request = request.body(body.to_string());
+        request = match (self.body_type(), self.body_content()) {
+            (GemonBodyType::None, _) => request,
+            (GemonBodyType::Multipart, _) => request.multipart(multipart::form(&self.form_data)?),
+            (GemonBodyType::FormUrlencoded, None) if !self.form_data.is_empty() => {
+                request.form(&multipart::text_fields(&self.form_data))
+            }
+            (_, Some(body)) => request.body(body),
+            (_, None) => request,
+        };
+        Ok(request.headers(self.headers.clone().to_header_map()?))
+    }
+
+    /// Sends the request, retrying connection errors, timeouts and the configured statuses
+    /// with an exponential backoff. Cookies of the selected environment are sent along and the
+    /// ones set by the responses are stored back into the project.
+    async fn send(&self) -> Result<(reqwest::Response, SentRequest), Box<dyn Error>> {
+        let proxy = match &self.proxy {
+            Some(url) => ProxySettings::from_url(url),
+            None => proxy_settings(),
+        };
+        let cookies = Arc::new(CookieStoreMutex::new(load_cookie_jar()));
+        let client =
+            self.options
+                .client(&tls_settings().merged(&self.tls), &proxy, cookies.clone())?;
+        let retries = self.options.retries.unwrap_or_default();
+        let mut attempt = 0;
+        let (result, sent) = loop {
+            let mut request = self.request(&client)?.build()?;
+            add_default_headers(&mut request);
+            let sent = sent_request(&request, &cookies);
+            let result = client.execute(request).await;
+            let retry = match &result {
+                Ok(response) => self
+                    .options
+                    .retry_statuses
+                    .contains(&response.status().as_u16()),
+                Err(err) => err.is_connect() || err.is_timeout(),
+            };
+            if !retry || attempt >= retries {
+                break (result, sent);
+            }
+            tokio::time::sleep(self.options.backoff(attempt)).await;
+            attempt += 1;
+        };
+        save_cookie_jar(&cookies.lock().expect("Cookie jar lock poisoned"))?;
+        Ok((result?, sent))
+    }
+}
+
+/// Sets the headers the client and the connection would add on their own, so the headers
+/// shown by `--verbose` are the ones that are sent.
+fn add_default_headers(request: &mut reqwest::Request) {
+    let length = request
+        .body()
+        .and_then(reqwest::Body::as_bytes)
+        .map(<[u8]>::len);
+    let headers = request.headers_mut();
+    headers
+        .entry(ACCEPT)
+        .or_insert(HeaderValue::from_static("*/*"));
+    if let Some(length) = length {
+        headers
+            .entry(CONTENT_LENGTH)
+            .or_insert(HeaderValue::from(length));
+    }
+}
+
+/// The request line and headers of a built request. The client adds the cookies of the jar
+/// only when it executes the request, so they are looked up the same way here.
+fn sent_request(request: &reqwest::Request, cookies: &CookieStoreMutex) -> SentRequest {
+    let mut headers: Vec<(String, String)> = request
+        .headers()
+        .iter()
+        .map(|(key, value)| {
+            (
+                key.as_str().to_string(),
+                value.to_str().unwrap_or_default().to_string(),
+            )
+        })
+        .collect();
+    // The connection sets the host of the url, so a redirect to another host keeps working
+    if !request.headers().contains_key(HOST) {
+        if let Some(host) = request.url().host_str() {
+            let host = match request.url().port() {
+                Some(port) => format!("{host}:{port}"),
+                None => host.to_string(),
+            };
+            headers.push((HOST.as_str().to_string(), host));
+        }
+    }
+    if !request.headers().contains_key(COOKIE) {
+        if let Some(cookie) = cookies.cookies(request.url()) {
+            headers.push((
+                COOKIE.as_str().to_string(),
+                cookie.to_str().unwrap_or_default().to_string(),
+            ));
         }
+    }
+    SentRequest {
+        method: request.method().to_string(),
+        url: request.url().to_string(),
+        headers,
+    }
+}
 
-        let response = request.send().await?;
+impl GemonRequest for GemonRestRequest {
+    async fn execute(&self) -> Result<GemonResponse, Box<dyn std::error::Error>> {
+        let (response, sent) = self.send().await?;
         let status = response.status().as_u16();
+        let version = format!("{:?}", response.version());
         let headers = response
             .headers()
             .iter()
@@ -168,7 +460,9 @@ impl GemonRequest for GemonRestRequest {
             })
             .collect();
         let response_bytes = response.bytes().await?;
-        Ok(GemonResponse::new(response_bytes, status, headers))
+        Ok(GemonResponse::new(response_bytes, status, headers)
+            .with_version(version)
+            .with_request(sent))
     }
 
     fn json_metadata(&self) -> String {
@@ -182,11 +476,39 @@ impl GemonRequest for GemonRestRequest {
         self.body.clone().unwrap_or_default()
     }
 
+    fn body_bytes(&self) -> Vec<u8> {
+        match &self.binary_body {
+            Some(bytes) => bytes.clone(),
+            None => self.json_body().into_bytes(),
+        }
+    }
+
     fn request_type(&self) -> String {
         String::from("REST")
     }
 
     fn set_body(&mut self, body: Option<String>) {
-        self.body = body
+        self.body = body;
+        self.binary_body = None;
+    }
+
+    fn set_binary_body(&mut self, body: Vec<u8>) -> Result<(), ProjectError> {
+        self.body = None;
+        self.binary_body = Some(body);
+        Ok(())
+    }
+
+    /// Maps the files uploaded by a multipart body, other body types send `@` values as text.
+    fn map_paths(&mut self, map: &PathMap) -> Result<(), ProjectError> {
+        if self.body_type() != GemonBodyType::Multipart {
+            return Ok(());
+        }
+        for value in self.form_data.values_mut() {
+            if let Some(mut file) = multipart::FilePart::parse(value) {
+                file.path = map(&file.path)?;
+                *value = file.to_value();
+            }
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,156 @@
+use super::request_builder::PathMap;
+use crate::project::ProjectError;
+use reqwest::{tls, Certificate, ClientBuilder, Identity};
+use serde_derive::{Deserialize, Serialize};
+use std::{error::Error, fs};
+
+pub static TLS_SETTINGS: [&str; 5] = ["cert", "key", "cacert", "insecure", "min-version"];
+
+/// TLS settings of REST requests. They are stored per environment in the project and can be
+/// overridden for a single call.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TlsSettings {
+    /// Path of the PEM client certificate, used together with `key` for mutual TLS.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cert: Option<String>,
+    /// Path of the PEM PKCS#8 private key of the client certificate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// Path of a PEM bundle with extra certificate authorities to trust.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_cert: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub insecure: Option<bool>,
+    /// Minimum accepted TLS version, one of 1.0, 1.1, 1.2 or 1.3.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_version: Option<String>,
+}
+
+impl TlsSettings {
+    pub fn is_empty(&self) -> bool {
+        *self == TlsSettings::default()
+    }
+
+    /// Returns these settings with every value that is set in `overrides` replaced.
+    pub fn merged(&self, overrides: &TlsSettings) -> TlsSettings {
+        TlsSettings {
+            cert: overrides.cert.clone().or(self.cert.clone()),
+            key: overrides.key.clone().or(self.key.clone()),
+            ca_cert: overrides.ca_cert.clone().or(self.ca_cert.clone()),
+            insecure: overrides.insecure.or(self.insecure),
+            min_version: overrides.min_version.clone().or(self.min_version.clone()),
+        }
+    }
+
+    /// Sets one setting by the name used on the command line, see `TLS_SETTINGS`.
+    pub fn set(&mut self, setting: &str, value: &str) -> Result<(), String> {
+        let value = value.trim();
+        match setting.trim() {
+            "cert" => self.cert = Some(value.to_string()),
+            "key" => self.key = Some(value.to_string()),
+            "cacert" => self.ca_cert = Some(value.to_string()),
+            "insecure" => {
+                self.insecure = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("insecure must be true or false, got '{value}'"))?,
+                )
+            }
+            "min-version" => {
+                parse_version(value)?;
+                self.min_version = Some(value.to_string());
+            }
+            other => {
+                return Err(format!(
+                    "Unknown TLS setting '{other}', expected one of: {}",
+                    TLS_SETTINGS.join(", ")
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces the certificate and key paths with `map(path)`.
+    pub fn map_paths(&mut self, map: &PathMap) -> Result<(), ProjectError> {
+        for path in [&mut self.cert, &mut self.key, &mut self.ca_cert]
+            .into_iter()
+            .flatten()
+        {
+            *path = map(path)?;
+        }
+        Ok(())
+    }
+
+    /// Applies the settings to a client builder, reading the certificate files.
+    pub fn configure(&self, mut builder: ClientBuilder) -> Result<ClientBuilder, Box<dyn Error>> {
+        match (&self.cert, &self.key) {
+            (Some(cert), Some(key)) => {
+                let identity = Identity::from_pkcs8_pem(&read(cert)?, &read(key)?)?;
+                builder = builder.identity(identity);
+            }
+            (None, None) => {}
+            _ => return Err("A client certificate needs both a cert and a key".into()),
+        }
+        if let Some(ca_cert) = &self.ca_cert {
+            for certificate in Certificate::from_pem_bundle(&read(ca_cert)?)? {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+        if let Some(insecure) = self.insecure {
+            builder = builder.danger_accept_invalid_certs(insecure);
+        }
+        if let Some(version) = &self.min_version {
+            builder = builder.min_tls_version(parse_version(version)?);
+        }
+        Ok(builder)
+    }
+}
+
+fn read(path: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    fs::read(path).map_err(|err| format!("Could not read {path}: {err}").into())
+}
+
+fn parse_version(version: &str) -> Result<tls::Version, String> {
+    match version.trim() {
+        "1.0" => Ok(tls::Version::TLS_1_0),
+        "1.1" => Ok(tls::Version::TLS_1_1),
+        "1.2" => Ok(tls::Version::TLS_1_2),
+        "1.3" => Ok(tls::Version::TLS_1_3),
+        other => Err(format!(
+            "Unsupported TLS version '{other}', expected 1.0, 1.1, 1.2 or 1.3"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TlsSettings;
+
+    #[test]
+    fn call_settings_override_environment_settings() {
+        let mut environment = TlsSettings::default();
+        environment.set("cacert", "ca.pem").unwrap();
+        environment.set("insecure", "false").unwrap();
+        environment.set("min-version", "1.2").unwrap();
+        let overrides = TlsSettings {
+            insecure: Some(true),
+            ..TlsSettings::default()
+        };
+
+        let merged = environment.merged(&overrides);
+
+        assert_eq!(merged.ca_cert.as_deref(), Some("ca.pem"));
+        assert_eq!(merged.insecure, Some(true));
+        assert_eq!(merged.min_version.as_deref(), Some("1.2"));
+        assert!(environment.set("min-version", "2").is_err());
+        assert!(environment.set("password", "secret").is_err());
+
+        environment.set("cert", "certs/client.pem").unwrap();
+        environment
+            .map_paths(&|path| Ok(format!("../../{path}")))
+            .unwrap();
+        assert_eq!(environment.cert.as_deref(), Some("../../certs/client.pem"));
+        assert_eq!(environment.ca_cert.as_deref(), Some("../../ca.pem"));
+        assert_eq!(environment.key, None);
+    }
+}
@@ -0,0 +1,294 @@
+use super::request_builder::{GemonRequest, GemonResponse, PathMap};
+use crate::{config::effector::Effector, project::ProjectError};
+use bytes::Bytes;
+use chrono::Local;
+use futures_util::{SinkExt, StreamExt};
+use serde_derive::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{collections::HashMap, error::Error, fs, time::Duration};
+use tokio::net::TcpStream;
+use tokio::time::{timeout_at, Instant};
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{
+        client::IntoClientRequest,
+        http::{HeaderName, HeaderValue},
+        Message,
+    },
+    MaybeTlsStream, WebSocketStream,
+};
+
+pub static DEFAULT_WEBSOCKET_TIMEOUT_MS: u64 = 5000;
+
+pub type GemonWebsocketStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+pub struct GemonWebsocketRequestBuilder {
+    url: Option<String>,
+    headers: HashMap<String, String>,
+    body: Option<String>,
+    frames_file: Option<String>,
+    timeout_ms: Option<u64>,
+    max_messages: Option<usize>,
+}
+
+impl GemonWebsocketRequestBuilder {
+    pub fn new() -> GemonWebsocketRequestBuilder {
+        GemonWebsocketRequestBuilder {
+            url: None,
+            headers: HashMap::new(),
+            body: None,
+            frames_file: None,
+            timeout_ms: None,
+            max_messages: None,
+        }
+    }
+
+    pub fn set_url(self, url: String) -> GemonWebsocketRequestBuilder {
+        GemonWebsocketRequestBuilder {
+            url: Some(url),
+            ..self
+        }
+    }
+
+    pub fn set_headers(self, headers: &HashMap<String, String>) -> GemonWebsocketRequestBuilder {
+        GemonWebsocketRequestBuilder {
+            headers: headers.clone(),
+            ..self
+        }
+    }
+
+    pub fn set_body(self, body: Option<String>) -> GemonWebsocketRequestBuilder {
+        GemonWebsocketRequestBuilder { body, ..self }
+    }
+
+    pub fn set_frames_file(self, frames_file: Option<String>) -> GemonWebsocketRequestBuilder {
+        GemonWebsocketRequestBuilder {
+            frames_file,
+            ..self
+        }
+    }
+
+    pub fn set_timeout_ms(self, timeout_ms: Option<u64>) -> GemonWebsocketRequestBuilder {
+        GemonWebsocketRequestBuilder { timeout_ms, ..self }
+    }
+
+    pub fn set_max_messages(self, max_messages: Option<usize>) -> GemonWebsocketRequestBuilder {
+        GemonWebsocketRequestBuilder {
+            max_messages,
+            ..self
+        }
+    }
+
+    pub fn build(&self) -> GemonWebsocketRequest {
+        GemonWebsocketRequest {
+            uri: String::from(
+                self.url
+                    .as_ref()
+                    .expect("Uri missing when building Websocket request!"),
+            ),
+            headers: self.headers.clone(),
+            body: self.body.clone(),
+            frames_file: self.frames_file.clone(),
+            timeout_ms: self.timeout_ms.unwrap_or(DEFAULT_WEBSOCKET_TIMEOUT_MS),
+            max_messages: self.max_messages,
+        }
+    }
+
+    pub fn build_from_string(content: &str) -> serde_json::Result<GemonWebsocketRequest> {
+        serde_json::from_str(content)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GemonWebsocketRequest {
+    uri: String,
+    headers: HashMap<String, String>,
+    body: Option<String>,
+    frames_file: Option<String>,
+    timeout_ms: u64,
+    max_messages: Option<usize>,
+}
+
+impl GemonWebsocketRequest {
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    pub fn headers(&self) -> &HashMap<String, String> {
+        &self.headers
+    }
+
+    pub fn body(&self) -> Option<&str> {
+        self.body.as_deref()
+    }
+
+    pub fn frames_file(&self) -> Option<&str> {
+        self.frames_file.as_deref()
+    }
+
+    pub fn timeout_ms(&self) -> u64 {
+        self.timeout_ms
+    }
+
+    pub fn max_messages(&self) -> Option<usize> {
+        self.max_messages
+    }
+
+    /// Performs the handshake and returns the open stream with the handshake status and headers.
+    pub async fn connect(
+        &self,
+    ) -> Result<(GemonWebsocketStream, u16, HashMap<String, String>), Box<dyn Error>> {
+        let mut request = self.uri.as_str().into_client_request()?;
+        for (key, value) in &self.headers {
+            request.headers_mut().insert(
+                HeaderName::from_bytes(key.as_bytes())?,
+                HeaderValue::from_str(value)?,
+            );
+        }
+
+        let (stream, handshake) = connect_async(request).await?;
+        let status = handshake.status().as_u16();
+        let headers = handshake
+            .headers()
+            .iter()
+            .map(|(key, value)| {
+                (
+                    key.as_str().to_string(),
+                    value.to_str().unwrap_or_default().to_string(),
+                )
+            })
+            .collect();
+        Ok((stream, status, headers))
+    }
+
+    /// Frames sent right after connecting: the body first, then every non-empty line of the
+    /// frames file.
+    pub fn initial_frames(&self) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut frames = match self.body.as_deref().map(str::trim) {
+            Some(body) if !body.is_empty() => body_to_frames(body),
+            _ => Vec::new(),
+        };
+
+        if let Some(path) = self.frames_file.as_ref() {
+            let content = Effector::apply_env_to_string(fs::read_to_string(path)?);
+            frames.extend(
+                content
+                    .lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .map(String::from),
+            );
+        }
+
+        Ok(frames)
+    }
+}
+
+/// A JSON array body is sent as one frame per element, anything else as a single frame.
+fn body_to_frames(body: &str) -> Vec<String> {
+    match serde_json::from_str::<Value>(body) {
+        Ok(Value::Array(values)) => values
+            .into_iter()
+            .map(|value| match value {
+                Value::String(text) => text,
+                other => other.to_string(),
+            })
+            .collect(),
+        _ => vec![body.to_string()],
+    }
+}
+
+fn message_to_json(message: Message) -> Option<Value> {
+    let received_at = Local::now().to_rfc3339();
+    match message {
+        Message::Text(text) => {
+            let data = serde_json::from_str::<Value>(text.as_str())
+                .unwrap_or_else(|_| Value::String(text.to_string()));
+            Some(json!({ "type": "text", "received_at": received_at, "data": data }))
+        }
+        Message::Binary(data) => Some(json!({
+            "type": "binary",
+            "received_at": received_at,
+            "size": data.len(),
+            "data": String::from_utf8_lossy(&data),
+        })),
+        _ => None,
+    }
+}
+
+impl GemonRequest for GemonWebsocketRequest {
+    async fn execute(&self) -> Result<GemonResponse, Box<dyn Error>> {
+        let (mut stream, status, headers) = self.connect().await?;
+        for frame in self.initial_frames()? {
+            stream.send(Message::text(frame)).await?;
+        }
+
+        let deadline = Instant::now() + Duration::from_millis(self.timeout_ms);
+        let mut messages = Vec::new();
+        while self
+            .max_messages
+            .map(|max| messages.len() < max)
+            .unwrap_or(true)
+        {
+            match timeout_at(deadline, stream.next()).await {
+                Ok(Some(message)) => {
+                    let message = message?;
+                    if message.is_close() {
+                        break;
+                    }
+                    if let Some(value) = message_to_json(message) {
+                        messages.push(value);
+                    }
+                }
+                Ok(None) | Err(_) => break,
+            }
+        }
+        let _ = stream.close(None).await;
+
+        let data = serde_json::to_vec(&Value::Array(messages))?;
+        Ok(GemonResponse::new(Bytes::from(data), status, headers))
+    }
+
+    fn json_metadata(&self) -> String {
+        let mut request_to_copy = self.clone();
+        request_to_copy.body.take();
+        serde_json::to_string_pretty(&request_to_copy)
+            .expect("Could not parse GemonWebsocketRequest to json string")
+    }
+
+    fn json_body(&self) -> String {
+        self.body.clone().unwrap_or_default()
+    }
+
+    fn request_type(&self) -> String {
+        String::from("WEBSOCKET")
+    }
+
+    fn set_body(&mut self, body: Option<String>) {
+        self.body = body
+    }
+
+    fn map_paths(&mut self, map: &PathMap) -> Result<(), ProjectError> {
+        if let Some(frames_file) = self.frames_file.as_mut() {
+            *frames_file = map(frames_file)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::body_to_frames;
+
+    #[test]
+    fn json_array_body_is_split_into_frames() {
+        let frames = body_to_frames(r#"["ping", {"subscribe": "prices"}]"#);
+
+        assert_eq!(frames, vec!["ping", r#"{"subscribe":"prices"}"#]);
+    }
+
+    #[test]
+    fn other_bodies_are_sent_as_a_single_frame() {
+        assert_eq!(body_to_frames(r#"{"a": 1}"#), vec![r#"{"a": 1}"#]);
+        assert_eq!(body_to_frames("hello"), vec!["hello"]);
+    }
+}
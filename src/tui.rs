@@ -2,8 +2,8 @@ use crate::EmptyResult;
 use app::{App, AppCommand};
 use crossterm::{
     event::{
-        self, Event, KeyEventKind, KeyboardEnhancementFlags, PopKeyboardEnhancementFlags,
-        PushKeyboardEnhancementFlags,
+        self, DisableBracketedPaste, EnableBracketedPaste, Event, KeyEventKind,
+        KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
     },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
@@ -18,12 +18,14 @@ use std::{
 mod app;
 mod input;
 mod ui;
+mod websocket;
 
 pub async fn run() -> EmptyResult {
     let mut terminal = TerminalSession::new()?;
     let mut app = App::new();
 
     loop {
+        app.poll_websocket();
         terminal.draw(&app)?;
 
         if app.should_quit {
@@ -31,13 +33,13 @@ pub async fn run() -> EmptyResult {
         }
 
         if event::poll(Duration::from_millis(150))? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
-                    match app.handle_key(key) {
-                        AppCommand::None => {}
-                        AppCommand::SendRequest => app.send_request().await,
-                    }
-                }
+            match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => match app.handle_key(key) {
+                    AppCommand::None => {}
+                    AppCommand::SendRequest => app.send_request().await,
+                },
+                Event::Paste(text) => app.handle_paste(text),
+                _ => {}
             }
         }
     }
@@ -54,7 +56,7 @@ impl TerminalSession {
     fn new() -> Result<TerminalSession, Box<dyn Error>> {
         enable_raw_mode()?;
         let mut stdout = io::stdout();
-        execute!(stdout, EnterAlternateScreen)?;
+        execute!(stdout, EnterAlternateScreen, EnableBracketedPaste)?;
         let keyboard_enhancement_enabled = execute!(
             stdout,
             PushKeyboardEnhancementFlags(
@@ -86,7 +88,11 @@ impl Drop for TerminalSession {
         if self.keyboard_enhancement_enabled {
             let _ = execute!(self.terminal.backend_mut(), PopKeyboardEnhancementFlags);
         }
-        let _ = execute!(self.terminal.backend_mut(), LeaveAlternateScreen);
+        let _ = execute!(
+            self.terminal.backend_mut(),
+            DisableBracketedPaste,
+            LeaveAlternateScreen
+        );
         let _ = self.terminal.show_cursor();
     }
 }
@@ -108,6 +108,18 @@ impl TextInput {
         }
     }
 
+    /// Inserts pasted text at the cursor. Single line inputs get spaces instead of newlines.
+    pub fn insert_text(&mut self, text: &str) {
+        for character in text.chars() {
+            match character {
+                '\r' => {}
+                '\n' if self.multiline => self.insert_newline(),
+                '\n' => self.insert_char(' '),
+                character => self.insert_char(character),
+            }
+        }
+    }
+
     fn insert_char(&mut self, character: char) {
         let row = self.cursor_row;
         let mut chars = self.lines[row].chars().collect::<Vec<_>>();
@@ -238,6 +250,18 @@ mod tests {
         assert_eq!(input.value(), "abc");
     }
 
+    #[test]
+    fn pasted_text_keeps_lines_only_in_multiline_inputs() {
+        let mut single = TextInput::single("a");
+        let mut multiline = TextInput::multiline("a");
+
+        single.insert_text("b\r\nc");
+        multiline.insert_text("b\r\nc");
+
+        assert_eq!(single.value(), "ab c");
+        assert_eq!(multiline.value(), "ab\nc");
+    }
+
     #[test]
     fn single_line_input_replaces_newlines() {
         let input = TextInput::single("one\ntwo");
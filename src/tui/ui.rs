@@ -1,6 +1,14 @@
 use super::{
-    app::{App, EnvField, Focus, KeyValue, Modal, PairField, RequestDraft, StatusKind, Tab},
+    app::{
+        App, EnvField, EnvironmentView, Focus, KeyValue, Modal, PairField, RequestDraft,
+        SavedRequestRow, StatusKind, Tab, REQUEST_OPTION_LABELS,
+    },
     input::TextInput,
+    websocket::LogDirection,
+};
+use crate::{
+    printer::highlight::{tokenize, Syntax, TokenKind},
+    request::multipart,
 };
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
@@ -59,6 +67,12 @@ fn draw_header(frame: &mut Frame<'_>, app: &App, area: Rect) {
         .as_deref()
         .map(|name| format!("Env: {name}"))
         .unwrap_or_else(|| String::from("Env: default"));
+    let proxy = app
+        .project
+        .proxy
+        .as_deref()
+        .map(|url| format!("Proxy: {url}  "))
+        .unwrap_or_default();
     let auth = if app.project.default_authorization_set {
         "Default auth: set"
     } else {
@@ -89,6 +103,7 @@ fn draw_header(frame: &mut Frame<'_>, app: &App, area: Rect) {
         Span::raw("  "),
         Span::styled(env, Style::default().fg(Color::Cyan)),
         Span::raw("  "),
+        Span::styled(proxy, Style::default().fg(Color::Magenta)),
         Span::styled(auth, Style::default().fg(Color::Yellow)),
     ]))
     .alignment(Alignment::Right)
@@ -113,22 +128,44 @@ fn draw_saved_requests(frame: &mut Frame<'_>, app: &App, area: Rect) {
             Style::default().fg(Color::DarkGray),
         )))]
     } else {
-        app.saved_requests
-            .iter()
-            .map(|request| {
-                ListItem::new(Line::from(vec![
-                    Span::styled(
-                        request.name.clone(),
-                        Style::default()
-                            .fg(Color::White)
-                            .add_modifier(Modifier::BOLD),
-                    ),
-                    Span::raw(" "),
-                    Span::styled(
-                        request.request_type.clone(),
-                        Style::default().fg(Color::DarkGray),
-                    ),
-                ]))
+        app.saved_request_rows()
+            .into_iter()
+            .map(|row| match row {
+                SavedRequestRow::Collection {
+                    path,
+                    depth,
+                    collapsed,
+                } => {
+                    let marker = if collapsed { "▸" } else { "▾" };
+                    let name = path.rsplit('/').next().unwrap_or_default();
+                    ListItem::new(Line::from(vec![
+                        Span::raw("  ".repeat(depth)),
+                        Span::styled(
+                            format!("{marker} {name}/"),
+                            Style::default()
+                                .fg(Color::Yellow)
+                                .add_modifier(Modifier::BOLD),
+                        ),
+                    ]))
+                }
+                SavedRequestRow::Request { index, depth } => {
+                    let request = &app.saved_requests[index];
+                    let name = request.name.rsplit('/').next().unwrap_or_default();
+                    ListItem::new(Line::from(vec![
+                        Span::raw("  ".repeat(depth)),
+                        Span::styled(
+                            name.to_string(),
+                            Style::default()
+                                .fg(Color::White)
+                                .add_modifier(Modifier::BOLD),
+                        ),
+                        Span::raw(" "),
+                        Span::styled(
+                            request.request_type.clone(),
+                            Style::default().fg(Color::DarkGray),
+                        ),
+                    ]))
+                }
             })
             .collect()
     };
@@ -155,10 +192,15 @@ fn draw_saved_requests(frame: &mut Frame<'_>, app: &App, area: Rect) {
 }
 
 fn draw_request_workspace(frame: &mut Frame<'_>, app: &App, area: Rect) {
+    if app.draft.is_websocket() {
+        draw_websocket_workspace(frame, app, area);
+        return;
+    }
+
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(7),
+            Constraint::Length(8),
             Constraint::Length(9),
             Constraint::Percentage(42),
             Constraint::Percentage(58),
@@ -177,14 +219,16 @@ fn draw_request_workspace(frame: &mut Frame<'_>, app: &App, area: Rect) {
         &app.draft.headers,
         app.draft.selected_header,
         app.focus == Focus::Headers,
+        false,
         pairs[0],
     );
     draw_pair_table(
         frame,
-        "Form Data  Ctrl-6",
+        "Form Data  Ctrl-6 | f file",
         &app.draft.form_data,
         app.draft.selected_form_data,
         app.focus == Focus::FormData,
+        true,
         pairs[1],
     );
 
@@ -192,23 +236,134 @@ fn draw_request_workspace(frame: &mut Frame<'_>, app: &App, area: Rect) {
     draw_response(frame, app, chunks[3]);
 }
 
+fn draw_websocket_workspace(frame: &mut Frame<'_>, app: &App, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(7),
+            Constraint::Length(9),
+            Constraint::Min(0),
+            Constraint::Length(3),
+        ])
+        .split(area);
+
+    draw_composer(frame, app, chunks[0]);
+
+    let pairs = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunks[1]);
+    draw_pair_table(
+        frame,
+        "Headers  Ctrl-5",
+        &app.draft.headers,
+        app.draft.selected_header,
+        app.focus == Focus::Headers,
+        false,
+        pairs[0],
+    );
+    let frames = Paragraph::new(display_multiline_input(
+        &app.draft.body,
+        app.focus == Focus::Body,
+    ))
+    .wrap(Wrap { trim: false })
+    .block(focused_block(
+        "Initial Frames  Ctrl-7",
+        app.focus == Focus::Body,
+    ));
+    frame.render_widget(frames, pairs[1]);
+
+    draw_session_log(frame, app, chunks[2]);
+
+    let focused = app.focus == Focus::Message;
+    let message = Paragraph::new(display_single_input(&app.draft.message, focused))
+        .block(focused_block("Message  Ctrl-6  Enter sends", focused));
+    frame.render_widget(message, chunks[3]);
+}
+
+fn draw_session_log(frame: &mut Frame<'_>, app: &App, area: Rect) {
+    let focused = app.active_tab == Tab::Requests && app.focus == Focus::SessionLog;
+    if app.session_log.is_empty() {
+        let empty = Paragraph::new("Not connected. Press Ctrl-R to connect.")
+            .style(Style::default().fg(Color::DarkGray))
+            .alignment(Alignment::Center)
+            .block(focused_block("Session  Ctrl-8", focused));
+        frame.render_widget(empty, area);
+        return;
+    }
+
+    let lines = app
+        .session_log
+        .iter()
+        .map(|entry| {
+            let (arrow, style) = match entry.direction {
+                LogDirection::Sent => (">>", Style::default().fg(Color::Cyan)),
+                LogDirection::Received => ("<<", Style::default().fg(Color::Green)),
+                LogDirection::Info => ("--", Style::default().fg(Color::DarkGray)),
+            };
+            Line::from(vec![
+                Span::styled(
+                    entry.timestamp.clone(),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::raw(" "),
+                Span::styled(arrow, style.add_modifier(Modifier::BOLD)),
+                Span::raw(" "),
+                Span::styled(entry.text.clone(), style),
+            ])
+        })
+        .collect::<Vec<_>>();
+
+    let log = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .scroll((app.session_scroll, 0))
+        .block(focused_block("Session  Ctrl-8", focused));
+    frame.render_widget(log, area);
+}
+
 fn draw_composer(frame: &mut Frame<'_>, app: &App, area: Rect) {
     let draft = &app.draft;
     let secure = if draft.secure { "on" } else { "off" };
-    let lines = vec![
-        Line::from(vec![
+    let mut first_line = vec![
+        Span::styled("Type ", label_style(app.focus == Focus::RequestType)),
+        Span::styled(
+            format!("[{}]", draft.request_type),
+            value_style(app.focus == Focus::RequestType),
+        ),
+        Span::raw("  "),
+    ];
+    if !draft.is_websocket() {
+        first_line.extend([
             Span::styled("Method ", label_style(app.focus == Focus::Method)),
             Span::styled(
                 format!("[{}]", draft.method),
                 value_style(app.focus == Focus::Method),
             ),
             Span::raw("  "),
-            Span::styled("Secure ", label_style(app.focus == Focus::Secure)),
-            Span::styled(
-                format!("[{secure}]"),
-                value_style(app.focus == Focus::Secure),
-            ),
-        ]),
+        ]);
+    }
+    first_line.extend([
+        Span::styled("Secure ", label_style(app.focus == Focus::Secure)),
+        Span::styled(
+            format!("[{secure}]"),
+            value_style(app.focus == Focus::Secure),
+        ),
+    ]);
+    if draft.is_websocket() {
+        let (session, style) = if app.websocket.is_some() {
+            ("connected", Style::default().fg(Color::Green))
+        } else {
+            ("disconnected", Style::default().fg(Color::DarkGray))
+        };
+        first_line.extend([
+            Span::raw("  "),
+            Span::styled("Session ", Style::default().fg(Color::DarkGray)),
+            Span::styled(format!("[{session}]"), style),
+        ]);
+    }
+
+    let mut lines = vec![
+        Line::from(first_line),
         Line::from(vec![
             Span::styled("URI  ", label_style(app.focus == Focus::Url)),
             Span::styled(
@@ -223,11 +378,23 @@ fn draw_composer(frame: &mut Frame<'_>, app: &App, area: Rect) {
                 value_style(app.focus == Focus::RequestName),
             ),
         ]),
-        Line::from(vec![
-            Span::styled("CLI  ", Style::default().fg(Color::DarkGray)),
-            Span::styled(draft.command_preview(), Style::default().fg(Color::Gray)),
-        ]),
     ];
+    if !draft.is_websocket() {
+        let options = draft.option_args();
+        let summary = if options.is_empty() {
+            String::from("defaults  Ctrl-O")
+        } else {
+            format!("{}  Ctrl-O", options.join(" "))
+        };
+        lines.push(Line::from(vec![
+            Span::styled("Opts ", Style::default().fg(Color::DarkGray)),
+            Span::styled(summary, Style::default().fg(Color::Gray)),
+        ]));
+    }
+    lines.push(Line::from(vec![
+        Span::styled("CLI  ", Style::default().fg(Color::DarkGray)),
+        Span::styled(draft.command_preview(), Style::default().fg(Color::Gray)),
+    ]));
 
     let composer = Paragraph::new(lines)
         .wrap(Wrap { trim: false })
@@ -235,7 +402,11 @@ fn draw_composer(frame: &mut Frame<'_>, app: &App, area: Rect) {
             "Composer  Ctrl-2",
             matches!(
                 app.focus,
-                Focus::Method | Focus::Url | Focus::RequestName | Focus::Secure
+                Focus::RequestType
+                    | Focus::Method
+                    | Focus::Url
+                    | Focus::RequestName
+                    | Focus::Secure
             ),
         ));
     frame.render_widget(composer, area);
@@ -247,6 +418,7 @@ fn draw_pair_table(
     pairs: &[KeyValue],
     selected: usize,
     focused: bool,
+    show_parts: bool,
     area: Rect,
 ) {
     if pairs.is_empty() {
@@ -264,35 +436,73 @@ fn draw_pair_table(
         } else {
             Style::default()
         };
-        Row::new(vec![
-            Cell::from(pair.key.clone()),
-            Cell::from(pair.value.clone()),
-        ])
-        .style(style)
+        let mut cells = vec![Cell::from(pair.key.clone()), Cell::from(pair.value.clone())];
+        if show_parts {
+            let part = match multipart::is_file(&pair.value) {
+                true => "file",
+                false => "text",
+            };
+            cells.insert(1, Cell::from(part));
+        }
+        Row::new(cells).style(style)
     });
 
-    let table = Table::new(
-        rows,
-        [Constraint::Percentage(35), Constraint::Percentage(65)],
-    )
-    .header(
-        Row::new(vec![Cell::from("Key"), Cell::from("Value")])
-            .style(Style::default().fg(Color::Yellow)),
-    )
-    .block(focused_block(title, focused))
-    .column_spacing(1);
+    let (widths, header) = match show_parts {
+        true => (
+            vec![
+                Constraint::Percentage(30),
+                Constraint::Length(4),
+                Constraint::Fill(1),
+            ],
+            vec!["Key", "Part", "Value"],
+        ),
+        false => (
+            vec![Constraint::Percentage(35), Constraint::Percentage(65)],
+            vec!["Key", "Value"],
+        ),
+    };
+    let table = Table::new(rows, widths)
+        .header(
+            Row::new(header.into_iter().map(Cell::from).collect::<Vec<_>>())
+                .style(Style::default().fg(Color::Yellow)),
+        )
+        .block(focused_block(title, focused))
+        .column_spacing(1);
 
     frame.render_widget(table, area);
 }
 
 fn draw_body(frame: &mut Frame<'_>, draft: &RequestDraft, focused: bool, area: Rect) {
+    let title = match &draft.binary_body {
+        _ if draft.is_websocket() => String::from("Body  Ctrl-7"),
+        Some(bytes) if draft.body.value().is_empty() => format!(
+            "Body {} ({} bytes, typing replaces it)  Ctrl-7 | Ctrl-T type",
+            draft.body_type,
+            bytes.len()
+        ),
+        _ => format!("Body {}  Ctrl-7 | Ctrl-T type", draft.body_type),
+    };
     let body = Paragraph::new(display_multiline_input(&draft.body, focused))
         .wrap(Wrap { trim: false })
-        .block(focused_block("Body  Ctrl-7", focused));
+        .block(focused_block(title, focused));
     frame.render_widget(body, area);
 }
 
 fn draw_response(frame: &mut Frame<'_>, app: &App, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)])
+        .split(area);
+    let filter_focused = app.active_tab == Tab::Requests && app.focus == Focus::ResponseFilter;
+    let filter = Paragraph::new(display_single_input(&app.response_filter, filter_focused))
+        .style(value_style(filter_focused))
+        .block(focused_block(
+            "Filter  Ctrl-F | e.x .users[0].name, .items[].id, .items | length",
+            filter_focused,
+        ));
+    frame.render_widget(filter, chunks[0]);
+    let area = chunks[1];
+
     let focused = app.active_tab == Tab::Requests && app.focus == Focus::Response;
     let Some(response) = &app.response else {
         let empty = Paragraph::new("No response yet. Press Ctrl-R to send the current request.")
@@ -341,13 +551,50 @@ fn draw_response(frame: &mut Frame<'_>, app: &App, area: Rect) {
     .block(focused_block("Response  Ctrl-8", focused));
     frame.render_widget(metadata, response_chunks[0]);
 
-    let body = Paragraph::new(response.body.clone())
+    let body = match response.filtered_body(&app.response_filter.value()) {
+        Ok((body, syntax)) if app.highlight => Paragraph::new(highlighted_lines(&body, syntax)),
+        Ok((body, _)) => Paragraph::new(body),
+        Err(message) => Paragraph::new(message).style(Style::default().fg(Color::Red)),
+    };
+    let body = body
         .wrap(Wrap { trim: false })
         .scroll((app.response_scroll, 0))
         .block(Block::default().borders(Borders::LEFT | Borders::RIGHT | Borders::BOTTOM));
     frame.render_widget(body, response_chunks[1]);
 }
 
+fn token_style(kind: TokenKind) -> Style {
+    match kind {
+        TokenKind::Plain => Style::default(),
+        TokenKind::Key | TokenKind::Tag => Style::default()
+            .fg(Color::Blue)
+            .add_modifier(Modifier::BOLD),
+        TokenKind::String => Style::default().fg(Color::Green),
+        TokenKind::Number | TokenKind::Attribute => Style::default().fg(Color::Cyan),
+        TokenKind::Boolean => Style::default().fg(Color::Yellow),
+        TokenKind::Null => Style::default().fg(Color::Magenta),
+        TokenKind::Comment => Style::default().fg(Color::DarkGray),
+    }
+}
+
+/// Highlighted lines of a response body, tokens spanning lines are split at the line breaks.
+fn highlighted_lines(body: &str, syntax: Syntax) -> Vec<Line<'static>> {
+    let mut lines = vec![Vec::new()];
+    for (kind, piece) in tokenize(body, syntax) {
+        for (index, part) in piece.split('\n').enumerate() {
+            if index > 0 {
+                lines.push(Vec::new());
+            }
+            if !part.is_empty() {
+                if let Some(line) = lines.last_mut() {
+                    line.push(Span::styled(part.to_string(), token_style(kind)));
+                }
+            }
+        }
+    }
+    lines.into_iter().map(Line::from).collect()
+}
+
 fn draw_environments(frame: &mut Frame<'_>, app: &App, area: Rect) {
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
@@ -371,6 +618,7 @@ fn draw_env_list(frame: &mut Frame<'_>, app: &App, area: Rect) {
             .map(|env| {
                 let selected = if env.selected { "selected" } else { "" };
                 let auth = if env.authorization_set { "auth" } else { "" };
+                let tls = if env.tls_set { "tls" } else { "" };
                 ListItem::new(Line::from(vec![
                     Span::styled(
                         env.name.clone(),
@@ -382,6 +630,8 @@ fn draw_env_list(frame: &mut Frame<'_>, app: &App, area: Rect) {
                     Span::styled(selected, Style::default().fg(Color::Cyan)),
                     Span::raw(" "),
                     Span::styled(auth, Style::default().fg(Color::Yellow)),
+                    Span::raw(" "),
+                    Span::styled(tls, Style::default().fg(Color::Green)),
                 ]))
             })
             .collect()
@@ -419,14 +669,70 @@ fn draw_env_values(frame: &mut Frame<'_>, app: &App, area: Rect) {
         return;
     };
 
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(5),
+            Constraint::Length((env.cookies.len() as u16).clamp(1, 8) + 3),
+        ])
+        .split(area);
+
     draw_pair_table(
         frame,
         "Environment Values  Ctrl-0",
         &env.values,
         app.selected_env_value,
         focused,
-        area,
+        false,
+        chunks[0],
     );
+    draw_cookies(frame, env, chunks[1]);
+}
+
+fn draw_cookies(frame: &mut Frame<'_>, env: &EnvironmentView, area: Rect) {
+    let title = "Cookies  c clears";
+    if env.cookies.is_empty() {
+        let empty = Paragraph::new("No cookies stored for this environment.")
+            .style(Style::default().fg(Color::DarkGray))
+            .alignment(Alignment::Center)
+            .block(focused_block(title, false));
+        frame.render_widget(empty, area);
+        return;
+    }
+
+    let rows = env.cookies.iter().map(|cookie| {
+        Row::new(vec![
+            Cell::from(cookie.name.clone()),
+            Cell::from(cookie.value.clone()),
+            Cell::from(cookie.domain.clone()),
+            Cell::from(cookie.path.clone()),
+            Cell::from(
+                cookie
+                    .expires
+                    .clone()
+                    .unwrap_or_else(|| String::from("session")),
+            ),
+        ])
+    });
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(18),
+            Constraint::Percentage(30),
+            Constraint::Percentage(20),
+            Constraint::Percentage(10),
+            Constraint::Percentage(22),
+        ],
+    )
+    .header(
+        Row::new(vec!["Name", "Value", "Domain", "Path", "Expires"])
+            .style(Style::default().fg(Color::Yellow)),
+    )
+    .block(focused_block(title, false))
+    .column_spacing(1);
+
+    frame.render_widget(table, area);
 }
 
 fn draw_help(frame: &mut Frame<'_>, area: Rect) {
@@ -444,15 +750,23 @@ fn draw_help(frame: &mut Frame<'_>, area: Rect) {
             Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
         )),
         Line::from("Ctrl-R send | Ctrl-S save | Ctrl-N new draft | Ctrl-D delete saved request"),
-        Line::from("Saved list: Enter load | Method/Secure: Enter or Space changes value"),
+        Line::from("Ctrl-E export the REST draft as curl | Ctrl-U import a pasted curl command"),
+        Line::from("Saved list: Enter load or fold a collection | Left/Right fold/unfold"),
+        Line::from("Type/Method/Secure: Enter or Space changes value"),
+        Line::from("Method: c sets a custom verb such as PURGE or REPORT"),
+        Line::from("Ctrl-O sets timeouts, redirects and retries of the REST draft"),
+        Line::from("Ctrl-T cycles the body type: json, text, xml, form, multipart, binary, none"),
+        Line::from("WEBSOCKET type: Ctrl-R connects or disconnects | Message: Enter sends | Session: Up/Down scroll"),
         Line::from("Headers/Form Data: a add | e or Enter edit | x remove"),
-        Line::from("Response: Up/Down/PageUp/PageDown scroll"),
+        Line::from("Form Data: f switches a row between text and a file upload such as @./avatar.png"),
+        Line::from("Response: Up/Down/PageUp/PageDown scroll | Ctrl-F filter JSON with e.x .users[].name, Enter shows the result"),
         Line::from(""),
         Line::from(Span::styled(
             "Environments",
             Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
         )),
         Line::from("Enter selects environment | a adds value | e edits value | x removes | u sets authorization"),
+        Line::from("c clears the cookies stored for the environment"),
         Line::from("Environment placeholders such as {base_uri} are applied when requests run."),
         Line::from(""),
         Line::from("Ctrl-L reload workspace | Ctrl-C or Ctrl-Q quit | Esc backs up a tab or closes a modal"),
@@ -559,6 +873,48 @@ fn draw_modal(frame: &mut Frame<'_>, app: &App, modal: &Modal) {
             Line::from(""),
             Line::from("Press y or Enter to confirm. Press n or Esc to cancel."),
         ],
+        Modal::ConfirmClearCookies { env } => vec![
+            Line::from(format!("Clear the cookies stored for '{env}'?")),
+            Line::from(""),
+            Line::from("Press y or Enter to confirm. Press n or Esc to cancel."),
+        ],
+        Modal::CustomMethod { name } => vec![
+            field_line("Method", name, true),
+            Line::from(""),
+            Line::from("Any HTTP verb, e.g. PURGE or REPORT. Enter sets it. Esc cancels."),
+        ],
+        Modal::ImportCurl { command } => vec![
+            field_line("Command", command, true),
+            Line::from(""),
+            Line::from("Paste a curl command. Enter loads it into the composer. Esc cancels."),
+        ],
+        Modal::ExportCurl { command } => {
+            let mut lines = command
+                .lines()
+                .map(|line| Line::from(line.to_string()))
+                .collect::<Vec<_>>();
+            lines.push(Line::from(""));
+            lines.push(Line::from(
+                "Select the command to copy it. Enter or Esc closes.",
+            ));
+            lines
+        }
+        Modal::RequestOptions { inputs, active } => {
+            let mut lines = REQUEST_OPTION_LABELS
+                .iter()
+                .zip(inputs)
+                .enumerate()
+                .map(|(index, (label, input))| field_line(label, input, index == *active))
+                .collect::<Vec<_>>();
+            lines.push(Line::from(""));
+            lines.push(Line::from(
+                "Empty uses the default. Redirects 0 disables following them.",
+            ));
+            lines.push(Line::from(
+                "Retry on takes statuses such as 502,503. Tab changes field. Enter saves.",
+            ));
+            lines
+        }
     };
 
     let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
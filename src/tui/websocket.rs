@@ -0,0 +1,161 @@
+use crate::request::websocket_request::GemonWebsocketRequest;
+use chrono::Local;
+use futures_util::{SinkExt, StreamExt};
+use tokio::{
+    sync::mpsc::{self, error::TryRecvError, UnboundedReceiver, UnboundedSender},
+    task::JoinHandle,
+};
+use tokio_tungstenite::tungstenite::Message;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionEvent {
+    Connected(u16),
+    Sent(String),
+    Received(String),
+    Error(String),
+    Closed,
+}
+
+#[derive(Debug)]
+enum SessionCommand {
+    Send(String),
+    Close,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogDirection {
+    Sent,
+    Received,
+    Info,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionLogEntry {
+    pub timestamp: String,
+    pub direction: LogDirection,
+    pub text: String,
+}
+
+impl SessionLogEntry {
+    pub fn new(direction: LogDirection, text: impl Into<String>) -> SessionLogEntry {
+        SessionLogEntry {
+            timestamp: Local::now().format("%H:%M:%S%.3f").to_string(),
+            direction,
+            text: text.into(),
+        }
+    }
+}
+
+/// A WebSocket connection running on a background task. The TUI talks to it only through
+/// channels so the draw loop never blocks on the network.
+#[derive(Debug)]
+pub struct WebsocketSession {
+    commands: UnboundedSender<SessionCommand>,
+    events: UnboundedReceiver<SessionEvent>,
+    task: JoinHandle<()>,
+}
+
+impl WebsocketSession {
+    pub fn connect(request: GemonWebsocketRequest) -> WebsocketSession {
+        let (commands, command_receiver) = mpsc::unbounded_channel();
+        let (event_sender, events) = mpsc::unbounded_channel();
+        let task = tokio::spawn(run_session(request, command_receiver, event_sender));
+        WebsocketSession {
+            commands,
+            events,
+            task,
+        }
+    }
+
+    pub fn send(&self, text: String) -> bool {
+        self.commands.send(SessionCommand::Send(text)).is_ok()
+    }
+
+    pub fn close(&self) {
+        let _ = self.commands.send(SessionCommand::Close);
+    }
+
+    /// Returns the next pending event, or `Closed` once the background task is gone.
+    pub fn try_event(&mut self) -> Option<SessionEvent> {
+        match self.events.try_recv() {
+            Ok(event) => Some(event),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(SessionEvent::Closed),
+        }
+    }
+}
+
+impl Drop for WebsocketSession {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn run_session(
+    request: GemonWebsocketRequest,
+    mut commands: UnboundedReceiver<SessionCommand>,
+    events: UnboundedSender<SessionEvent>,
+) {
+    let (mut stream, status, _) = match request.connect().await {
+        Ok(connection) => connection,
+        Err(err) => {
+            let _ = events.send(SessionEvent::Error(err.to_string()));
+            let _ = events.send(SessionEvent::Closed);
+            return;
+        }
+    };
+    let _ = events.send(SessionEvent::Connected(status));
+
+    match request.initial_frames().map_err(|err| err.to_string()) {
+        Ok(frames) => {
+            for frame in frames {
+                if let Err(err) = stream.send(Message::text(frame.clone())).await {
+                    let _ = events.send(SessionEvent::Error(err.to_string()));
+                    break;
+                }
+                let _ = events.send(SessionEvent::Sent(frame));
+            }
+        }
+        Err(message) => {
+            let _ = events.send(SessionEvent::Error(message));
+        }
+    }
+
+    loop {
+        tokio::select! {
+            command = commands.recv() => match command {
+                Some(SessionCommand::Send(text)) => {
+                    if let Err(err) = stream.send(Message::text(text.clone())).await {
+                        let _ = events.send(SessionEvent::Error(err.to_string()));
+                        break;
+                    }
+                    let _ = events.send(SessionEvent::Sent(text));
+                }
+                Some(SessionCommand::Close) | None => {
+                    let _ = stream.close(None).await;
+                    break;
+                }
+            },
+            message = stream.next() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    let _ = events.send(SessionEvent::Received(text.to_string()));
+                }
+                Some(Ok(Message::Binary(data))) => {
+                    let _ = events.send(SessionEvent::Received(format!(
+                        "<{} bytes> {}",
+                        data.len(),
+                        String::from_utf8_lossy(&data)
+                    )));
+                }
+                Some(Ok(Message::Close(_))) | None => break,
+                Some(Ok(_)) => {}
+                Some(Err(err)) => {
+                    let _ = events.send(SessionEvent::Error(err.to_string()));
+                    break;
+                }
+            },
+        }
+    }
+
+    let _ = events.send(SessionEvent::Closed);
+}